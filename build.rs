@@ -1,17 +1,47 @@
 // build.rs - Run before compilation to create bundled version
-use std::process::Command;
+use bundler_lib::{BundlerConfig, CodeBundler, ProjectConfig};
+use std::path::Path;
 
 fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-env-changed=BUNDLE_CODE");
+
     // Only run bundling in release mode or when explicitly requested
-    if std::env::var("PROFILE").unwrap_or_default() == "release"
-        || std::env::var("BUNDLE_CODE").is_ok()
-    {
-        println!("cargo:rerun-if-changed=src/main.rs");
-        println!("cargo:rerun-if-changed=cp-lib/src/");
-
-        // Run the bundler
-        let _ = Command::new("cargo")
-            .args(&["run", "--bin", "ast_bundler"])
-            .status();
+    let requested = std::env::var("PROFILE").unwrap_or_default() == "release"
+        || std::env::var("BUNDLE_CODE").is_ok();
+    if !requested {
+        return;
+    }
+
+    if let Err(e) = bundle() {
+        println!("cargo:warning=Bundling failed: {}", e);
     }
 }
+
+fn bundle() -> Result<(), Box<dyn std::error::Error>> {
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR")?;
+    let manifest_dir = Path::new(&manifest_dir);
+
+    let config = match ProjectConfig::discover(manifest_dir) {
+        Some(path) => {
+            println!("cargo:rerun-if-changed={}", path.display());
+            ProjectConfig::load_bundler_config(&path, manifest_dir)?
+        }
+        None => BundlerConfig::default(),
+    };
+    println!("cargo:rerun-if-changed=Cargo.toml");
+    // Watched up front so a broken main file is retried once it is fixed.
+    println!("cargo:rerun-if-changed={}", config.main_file.display());
+
+    let mut bundler = CodeBundler::new(config);
+    let result = bundler.bundle();
+
+    for file in bundler.bundled_files() {
+        println!("cargo:rerun-if-changed={}", file.display());
+    }
+    for warning in bundler.warnings() {
+        println!("cargo:warning={}", warning);
+    }
+
+    result.map(|_| ())
+}
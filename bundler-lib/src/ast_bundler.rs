@@ -3,44 +3,83 @@
  * Please don't touch I will nuke the repo if you do
  */
 
-use crate::file_resolver::FileResolver;
-use std::collections::HashSet;
+use crate::cfg_eval::{item_attrs, CfgSet};
+use crate::file_resolver::{CrateMode, FileResolver};
+use crate::project_config::MinifyOptions;
+use std::collections::{BTreeSet, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 use syn::visit::Visit;
 use syn::visit_mut::VisitMut;
 use syn::{File as SynFile, Item, ItemUse, UseTree, UsePath};
+use proc_macro2::{Ident, TokenStream, TokenTree};
 use quote::ToTokens;
 
+/// Standard library imports every bundle starts with.
+const HEADER_IMPORTS: [&str; 5] = [
+    "std::io::self",
+    "std::io::BufRead",
+    "std::io::Cursor",
+    "std::collections::*",
+    "std::fmt::Debug",
+];
+
 pub struct AstBundler<'a> {
     resolver: &'a FileResolver,
     bundled_items: Vec<Item>,
     processed_modules: HashSet<String>,
-    use_statements: HashSet<String>,
-    cp_lib_aliases: HashSet<String>,
+    /// Library files read while bundling, in the order they were inlined.
+    source_files: Vec<PathBuf>,
+    use_statements: BTreeSet<String>,
+    lib_aliases: HashSet<String>,
+    /// Library crates whose used modules are hoisted to the top level.
+    flatten_crates: HashSet<String>,
+    /// Library crates bundled whole as `pub mod <name>`.
+    nested_crates: HashSet<String>,
+    /// Flattened crate whose module is being processed, so `crate::` paths resolve.
+    current_crate: Option<String>,
+    cfg: CfgSet,
+    minify: MinifyOptions,
 }
 
-struct CpLibPathCollector {
+struct LibPathCollector<'a> {
+    flatten_crates: &'a HashSet<String>,
+    nested_crates: &'a HashSet<String>,
+    current_crate: Option<&'a str>,
     paths: Vec<String>,
+    nested_used: Vec<String>,
 }
 
-impl CpLibPathCollector {
-    fn new() -> Self {
-        Self { paths: Vec::new() }
+impl<'a> LibPathCollector<'a> {
+    fn new(bundler: &'a AstBundler) -> Self {
+        Self {
+            flatten_crates: &bundler.flatten_crates,
+            nested_crates: &bundler.nested_crates,
+            current_crate: bundler.current_crate.as_deref(),
+            paths: Vec::new(),
+            nested_used: Vec::new(),
+        }
     }
 }
 
-impl<'ast> Visit<'ast> for CpLibPathCollector {
+impl<'ast> Visit<'ast> for LibPathCollector<'_> {
     fn visit_path(&mut self, path: &'ast syn::Path) {
         if let Some(first) = path.segments.first() {
-            if first.ident == "cp_lib" {
-                let joined = path
-                    .segments
-                    .iter()
-                    .map(|s| s.ident.to_string())
+            let first_ident = first.ident.to_string();
+            let crate_name = match self.current_crate {
+                Some(current) if first_ident == "crate" => Some(current),
+                _ if self.flatten_crates.contains(&first_ident) => Some(first_ident.as_str()),
+                _ => None,
+            };
+
+            if let Some(crate_name) = crate_name {
+                let joined = std::iter::once(crate_name.to_string())
+                    .chain(path.segments.iter().skip(1).map(|s| s.ident.to_string()))
                     .collect::<Vec<_>>()
                     .join("::");
                 self.paths.push(joined);
+            } else if self.nested_crates.contains(&first_ident) {
+                self.nested_used.push(first_ident);
             }
         }
         syn::visit::visit_path(self, path);
@@ -49,13 +88,75 @@ impl<'ast> Visit<'ast> for CpLibPathCollector {
 
 struct StripPaths<'a> {
     aliases: &'a HashSet<String>,
+    flatten_crates: &'a HashSet<String>,
+    in_flattened_crate: bool,
+}
+
+impl StripPaths<'_> {
+    /// Shortens library paths inside macro bodies the same way: in a flattened
+    /// crate `$crate::io::scanner::Scanner` becomes `$crate::Scanner`, and
+    /// `cp_lib::io::scanner::Scanner` becomes `Scanner` anywhere. Leading
+    /// lowercase segments count as modules; the last segment is always kept.
+    fn rewrite_tokens(&self, tokens: TokenStream) -> TokenStream {
+        let trees: Vec<TokenTree> = tokens.into_iter().collect();
+        let is_path_sep = |i: usize| {
+            matches!(
+                (trees.get(i), trees.get(i + 1)),
+                (Some(TokenTree::Punct(a)), Some(TokenTree::Punct(b))) if a.as_char() == ':' && b.as_char() == ':'
+            )
+        };
+        let is_module = |i: usize| {
+            matches!(&trees[i], TokenTree::Ident(ident)
+                if ident.to_string().starts_with(|c: char| c.is_ascii_lowercase()))
+                && is_path_sep(i + 1)
+                && matches!(trees.get(i + 3), Some(TokenTree::Ident(_)))
+        };
+
+        let mut out = Vec::with_capacity(trees.len());
+        let mut i = 0;
+        while i < trees.len() {
+            let tree = &trees[i];
+            match tree {
+                TokenTree::Group(group) => {
+                    let mut rewritten = proc_macro2::Group::new(group.delimiter(), self.rewrite_tokens(group.stream()));
+                    rewritten.set_span(group.span());
+                    out.push(TokenTree::Group(rewritten));
+                }
+                TokenTree::Ident(ident) if is_path_sep(i + 1) => {
+                    let name = ident.to_string();
+                    let after_dollar = matches!(out.last(), Some(TokenTree::Punct(p)) if p.as_char() == '$');
+                    let crate_root = name == "crate" && self.in_flattened_crate;
+                    let lib_crate = !after_dollar && self.flatten_crates.contains(&name);
+                    if !crate_root && !lib_crate {
+                        out.push(tree.clone());
+                        i += 1;
+                        continue;
+                    }
+
+                    if crate_root {
+                        out.extend(trees[i..i + 3].iter().cloned());
+                    }
+                    i += 3;
+                    while i < trees.len() && is_module(i) {
+                        i += 3;
+                    }
+                    continue;
+                }
+                _ => out.push(tree.clone()),
+            }
+            i += 1;
+        }
+        out.into_iter().collect()
+    }
 }
 
 impl<'a> VisitMut for StripPaths<'a> {
     fn visit_path_mut(&mut self, path: &mut syn::Path) {
         if let Some(first) = path.segments.first() {
             let first_ident = first.ident.to_string();
-            let should_strip = first.ident == "cp_lib" || (path.segments.len() > 1 && self.aliases.contains(&first_ident));
+            let is_lib_path = self.flatten_crates.contains(&first_ident)
+                || (self.in_flattened_crate && first_ident == "crate" && path.segments.len() > 1);
+            let should_strip = is_lib_path || (path.segments.len() > 1 && self.aliases.contains(&first_ident));
 
             if should_strip {
                 if let Some(last) = path.segments.last().cloned() {
@@ -68,6 +169,133 @@ impl<'a> VisitMut for StripPaths<'a> {
 
         syn::visit_mut::visit_path_mut(self, path);
     }
+
+    fn visit_macro_mut(&mut self, mac: &mut syn::Macro) {
+        syn::visit_mut::visit_macro_mut(self, mac);
+        mac.tokens = self.rewrite_tokens(std::mem::take(&mut mac.tokens));
+    }
+}
+
+/// Prefixes `crate::` paths with the module a nested crate is wrapped in.
+struct NestCratePaths {
+    name: Ident,
+}
+
+impl NestCratePaths {
+    /// Rewrites `crate ::` and `$crate ::` inside macro token streams.
+    fn rewrite_tokens(&self, tokens: TokenStream) -> TokenStream {
+        let trees: Vec<TokenTree> = tokens.into_iter().collect();
+        let mut out = Vec::with_capacity(trees.len());
+        for (i, tree) in trees.iter().enumerate() {
+            match tree {
+                TokenTree::Group(group) => {
+                    let mut rewritten =
+                        proc_macro2::Group::new(group.delimiter(), self.rewrite_tokens(group.stream()));
+                    rewritten.set_span(group.span());
+                    out.push(TokenTree::Group(rewritten));
+                }
+                TokenTree::Ident(ident) if ident == "crate" => {
+                    out.push(tree.clone());
+                    let followed_by_path = matches!(
+                        (trees.get(i + 1), trees.get(i + 2)),
+                        (Some(TokenTree::Punct(a)), Some(TokenTree::Punct(b)))
+                            if a.as_char() == ':' && b.as_char() == ':'
+                    );
+                    if followed_by_path {
+                        let name = &self.name;
+                        out.extend(quote::quote!(:: #name));
+                    }
+                }
+                _ => out.push(tree.clone()),
+            }
+        }
+        out.into_iter().collect()
+    }
+}
+
+impl VisitMut for NestCratePaths {
+    fn visit_path_mut(&mut self, path: &mut syn::Path) {
+        if path.segments.len() > 1 && path.segments[0].ident == "crate" {
+            path.segments.insert(1, syn::PathSegment::from(self.name.clone()));
+        }
+        syn::visit_mut::visit_path_mut(self, path);
+    }
+
+    fn visit_use_path_mut(&mut self, use_path: &mut UsePath) {
+        if use_path.ident == "crate" {
+            let inner = std::mem::replace(&mut *use_path.tree, UseTree::Glob(syn::UseGlob {
+                star_token: Default::default(),
+            }));
+            *use_path.tree = UseTree::Path(UsePath {
+                ident: self.name.clone(),
+                colon2_token: Default::default(),
+                tree: Box::new(inner),
+            });
+            return;
+        }
+        syn::visit_mut::visit_use_path_mut(self, use_path);
+    }
+
+    fn visit_visibility_mut(&mut self, _vis: &mut syn::Visibility) {
+        // `pub(crate)` keeps meaning the whole submission.
+    }
+
+    fn visit_macro_mut(&mut self, mac: &mut syn::Macro) {
+        mac.tokens = self.rewrite_tokens(std::mem::take(&mut mac.tokens));
+    }
+}
+
+struct StripDocComments;
+
+impl VisitMut for StripDocComments {
+    fn visit_attributes_mut(&mut self, attrs: &mut Vec<syn::Attribute>) {
+        attrs.retain(|attr| !attr.path().is_ident("doc"));
+    }
+}
+
+/// Whether `item` is a `macro_rules!` whose every rule expands to nothing,
+/// like `debug!` outside local builds.
+fn is_noop_macro(item: &syn::ItemMacro) -> bool {
+    if item.ident.is_none() || !item.mac.path.is_ident("macro_rules") {
+        return false;
+    }
+    let trees: Vec<TokenTree> = item.mac.tokens.clone().into_iter().collect();
+    let bodies: Vec<&proc_macro2::Group> = trees
+        .windows(3)
+        .filter_map(|window| match window {
+            [TokenTree::Punct(eq), TokenTree::Punct(gt), TokenTree::Group(body)]
+                if eq.as_char() == '=' && gt.as_char() == '>' =>
+            {
+                Some(body)
+            }
+            _ => None,
+        })
+        .collect();
+    !bodies.is_empty() && bodies.iter().all(|body| body.stream().is_empty())
+}
+
+/// Removes statements that only call a no-op macro, so their arguments do
+/// not reach the bundle.
+struct StripNoopMacroCalls<'a> {
+    names: &'a HashSet<String>,
+}
+
+impl StripNoopMacroCalls<'_> {
+    fn is_call(&self, stmt: &syn::Stmt) -> bool {
+        let path = match stmt {
+            syn::Stmt::Macro(stmt) => &stmt.mac.path,
+            syn::Stmt::Expr(syn::Expr::Macro(expr), Some(_)) => &expr.mac.path,
+            _ => return false,
+        };
+        path.segments.last().is_some_and(|segment| self.names.contains(&segment.ident.to_string()))
+    }
+}
+
+impl VisitMut for StripNoopMacroCalls<'_> {
+    fn visit_block_mut(&mut self, block: &mut syn::Block) {
+        block.stmts.retain(|stmt| !self.is_call(stmt));
+        syn::visit_mut::visit_block_mut(self, block);
+    }
 }
 
 impl<'a> AstBundler<'a> {
@@ -76,50 +304,112 @@ impl<'a> AstBundler<'a> {
             resolver,
             bundled_items: Vec::new(),
             processed_modules: HashSet::new(),
-            use_statements: HashSet::new(),
-            cp_lib_aliases: HashSet::new(),
+            source_files: Vec::new(),
+            use_statements: BTreeSet::new(),
+            lib_aliases: HashSet::new(),
+            flatten_crates: resolver
+                .crates()
+                .filter(|krate| krate.mode == CrateMode::Flatten)
+                .map(|krate| krate.name.clone())
+                .collect(),
+            nested_crates: resolver
+                .crates()
+                .filter(|krate| krate.mode == CrateMode::Nested)
+                .map(|krate| krate.name.clone())
+                .collect(),
+            current_crate: None,
+            cfg: CfgSet::default(),
+            minify: MinifyOptions::default(),
+        }
+    }
+
+    /// Library files that ended up in the bundle.
+    pub fn source_files(&self) -> &[PathBuf] {
+        &self.source_files
+    }
+
+    /// Sets the cfg options assumed on the judge; disabled items are dropped.
+    pub fn set_cfg(&mut self, cfg: CfgSet) {
+        self.cfg = cfg;
+    }
+
+    pub fn set_minify(&mut self, minify: MinifyOptions) {
+        self.minify = minify;
+    }
+
+    /// Clones `item` with cfg-disabled parts removed, or `None` if the whole
+    /// item is disabled on the judge.
+    fn prepare_item(&self, item: &Item) -> Option<Item> {
+        if !self.cfg.is_enabled(item_attrs(item)) {
+            return None;
         }
+        let mut cloned = item.clone();
+        self.cfg.apply_to_item(&mut cloned);
+        if self.minify.strip_doc_comments {
+            StripDocComments.visit_item_mut(&mut cloned);
+        }
+        Some(cloned)
     }
 
-    fn inline_cp_lib_paths_in_item(&mut self, item: &Item) -> Result<(), Box<dyn std::error::Error>> {
-        let mut collector = CpLibPathCollector::new();
+    fn push_item(&mut self, item: &Item) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(mut prepared) = self.prepare_item(item) else {
+            return Ok(());
+        };
+        self.inline_lib_paths_in_item(&prepared)?;
+        self.rewrite_lib_paths_in_item(&mut prepared);
+        self.bundled_items.push(prepared);
+        Ok(())
+    }
+
+    fn inline_lib_paths_in_item(&mut self, item: &Item) -> Result<(), Box<dyn std::error::Error>> {
+        let mut collector = LibPathCollector::new(self);
         collector.visit_item(item);
-        for path in collector.paths {
-            if path.starts_with("cp_lib::") {
-                self.resolve_and_inline_module(&path)?;
-            }
+        let (paths, nested_used) = (collector.paths, collector.nested_used);
+        for path in paths {
+            self.resolve_and_inline_module(&path)?;
+        }
+        for name in nested_used {
+            self.inline_nested_crate(&name)?;
         }
         Ok(())
     }
 
-    fn rewrite_cp_lib_paths_in_item(&self, item: &mut Item) {
+    fn rewrite_lib_paths_in_item(&self, item: &mut Item) {
         let mut rewriter = StripPaths {
-            aliases: &self.cp_lib_aliases,
+            aliases: &self.lib_aliases,
+            flatten_crates: &self.flatten_crates,
+            in_flattened_crate: self.current_crate.is_some(),
         };
         rewriter.visit_item_mut(item);
     }
 
+    /// Maps the first segment of a path to the flattened crate it refers to.
+    fn flattened_crate_of(&self, first_segment: &str) -> Option<String> {
+        if first_segment == "crate" {
+            return self.current_crate.clone();
+        }
+        self.flatten_crates
+            .contains(first_segment)
+            .then(|| first_segment.to_string())
+    }
+
     pub fn process_file_ast(&mut self, ast: &SynFile, _file_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
         for item in &ast.items {
             match item {
                 Item::Use(use_item) => {
-                    self.process_use_item(use_item)?;
+                    if self.cfg.is_enabled(&use_item.attrs) {
+                        self.process_use_item(use_item)?;
+                    }
                 }
                 Item::Const(const_item) => {
                     // Skip _PROBLEM constant as it's not needed in final bundle
                     if const_item.ident != "_PROBLEM" {
-                        self.inline_cp_lib_paths_in_item(item)?;
-                        let mut cloned = item.clone();
-                        self.rewrite_cp_lib_paths_in_item(&mut cloned);
-                        self.bundled_items.push(cloned);
+                        self.push_item(item)?;
                     }
                 }
                 _ => {
                     // Include all other items (functions, structs, etc.)
-                    self.inline_cp_lib_paths_in_item(item)?;
-                    let mut cloned = item.clone();
-                    self.rewrite_cp_lib_paths_in_item(&mut cloned);
-                    self.bundled_items.push(cloned);
+                    self.push_item(item)?;
                 }
             }
         }
@@ -131,27 +421,36 @@ impl<'a> AstBundler<'a> {
         let mut use_paths = Vec::new();
         Self::collect_use_paths(String::new(), &use_item.tree, &mut use_paths);
 
-        for path in use_paths {
-            if path.starts_with("cp_lib::") {
+        for (path, alias) in use_paths {
+            let (first, rest) = path.split_once("::").unwrap_or((path.as_str(), ""));
+            if let Some(crate_name) = self.flattened_crate_of(first).filter(|_| !rest.is_empty()) {
                 if let Some(alias) = path.split("::").last() {
                     if alias != "*" {
                         // Heuristic: only treat lowercase-leading names as module aliases.
                         // This prevents rewriting paths like `Scanner::from_reader`.
                         if alias.chars().next().is_some_and(|c| c.is_ascii_lowercase()) {
-                            self.cp_lib_aliases.insert(alias.to_string());
+                            self.lib_aliases.insert(alias.to_string());
                         }
                     }
                 }
-                // This is a cp_lib import, resolve and inline it
-                self.resolve_and_inline_module(&path)?;
+                // This is a library import, resolve and inline it
+                self.resolve_and_inline_module(&format!("{}::{}", crate_name, rest))?;
+            } else if self.nested_crates.contains(first) {
+                // The crate keeps its module tree, so the import stays as written.
+                self.inline_nested_crate(first)?;
+                self.use_statements.insert(use_item.to_token_stream().to_string());
             } else if path.starts_with("std::") {
-                // Standard library import, keep it but avoid duplicates
-                let use_stmt = use_item.to_token_stream().to_string();
-                let normalized: String = use_stmt.chars().filter(|c| !c.is_whitespace()).collect();
-                // Skip std::io imports as we add our own comprehensive ones
-                if !normalized.contains("std::io") {
-                    self.use_statements.insert(use_stmt);
+                // Standard library import, one statement per name so the ones
+                // already in the bundle header can be dropped without losing the rest.
+                if alias.is_none() && HEADER_IMPORTS.contains(&path.as_str()) {
+                    continue;
                 }
+                let path = path.strip_suffix("::self").unwrap_or(&path);
+                let use_stmt = match alias {
+                    Some(alias) => format!("use {} as {};", path, alias),
+                    None => format!("use {};", path),
+                };
+                self.use_statements.insert(use_stmt);
             }
             // Skip other external crate imports
         }
@@ -159,7 +458,8 @@ impl<'a> AstBundler<'a> {
         Ok(())
     }
 
-    fn collect_use_paths(prefix: String, tree: &UseTree, out: &mut Vec<String>) {
+    /// Flattens a use tree into full paths, each with its `as` alias if renamed.
+    fn collect_use_paths(prefix: String, tree: &UseTree, out: &mut Vec<(String, Option<String>)>) {
         match tree {
             UseTree::Path(UsePath { ident, tree, .. }) => {
                 let next_prefix = if prefix.is_empty() {
@@ -175,7 +475,7 @@ impl<'a> AstBundler<'a> {
                 } else {
                     format!("{}::{}", prefix, name.ident)
                 };
-                out.push(full);
+                out.push((full, None));
             }
             UseTree::Rename(rename) => {
                 let full = if prefix.is_empty() {
@@ -183,7 +483,7 @@ impl<'a> AstBundler<'a> {
                 } else {
                     format!("{}::{}", prefix, rename.ident)
                 };
-                out.push(full);
+                out.push((full, Some(rename.rename.to_string())));
             }
             UseTree::Glob(_) => {
                 let full = if prefix.is_empty() {
@@ -191,7 +491,7 @@ impl<'a> AstBundler<'a> {
                 } else {
                     format!("{}::*", prefix)
                 };
-                out.push(full);
+                out.push((full, None));
             }
             UseTree::Group(group) => {
                 for item in &group.items {
@@ -205,9 +505,10 @@ impl<'a> AstBundler<'a> {
         // Convert cp_lib::io::scanner::Scanner to file path
         let mut parts: Vec<&str> = module_path.split("::").collect();
         
-        if parts.len() < 2 || parts[0] != "cp_lib" {
+        if parts.len() < 2 || !self.flatten_crates.contains(parts[0]) {
             return Ok(());
         }
+        let crate_name = parts[0].to_string();
 
         // Handle glob imports like cp_lib::algorithms::* by treating it as the module itself.
         if parts.last().is_some_and(|p| *p == "*") {
@@ -238,48 +539,130 @@ impl<'a> AstBundler<'a> {
         }
 
         for pattern in file_patterns {
-            if let Some(resolved_path) = self.resolver.resolve_module_file(&PathBuf::from(&pattern)) {
-                let module_key = resolved_path.to_string_lossy().to_string();
-                
-                if !self.processed_modules.contains(&module_key) {
-                    self.processed_modules.insert(module_key.clone());
-                    
-                    // Read and parse the module file
-                    let content = fs::read_to_string(&resolved_path)?;
-                    let module_ast: SynFile = syn::parse_str(&content)?;
-                    
-                    // Process the module recursively
-                    self.process_module_ast(&module_ast, &resolved_path)?;
-                    
+            if let Some(resolved_path) = self.resolver.resolve_in_crate(&crate_name, &PathBuf::from(&pattern)) {
+                if self.inline_module_file(&crate_name, resolved_path)? {
                     // Stop after first successful resolution
                     return Ok(());
                 }
             }
         }
+
+        // `cp_lib::input` may name an exported macro defined in any module.
+        if parts.len() == 2 {
+            if let Some(resolved_path) = self.resolver.find_exported_macro(&crate_name, parts[1]) {
+                self.inline_module_file(&crate_name, resolved_path)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Inlines a module file of a flattened crate unless it already was.
+    /// Returns whether the file was inlined now.
+    fn inline_module_file(&mut self, crate_name: &str, resolved_path: PathBuf) -> Result<bool, Box<dyn std::error::Error>> {
+        let module_key = resolved_path.to_string_lossy().to_string();
+        if !self.processed_modules.insert(module_key) {
+            return Ok(false);
+        }
+
+        // Read and parse the module file
+        self.source_files.push(resolved_path.clone());
+        let content = fs::read_to_string(&resolved_path)?;
+        let module_ast: SynFile = syn::parse_str(&content)?;
+
+        // Process the module recursively
+        let outer_crate = self.current_crate.replace(crate_name.to_string());
+        let result = self.process_module_ast(&module_ast, &resolved_path);
+        self.current_crate = outer_crate;
+        result?;
+        Ok(true)
+    }
+
+    /// Bundles a whole crate as `pub mod <name> { ... }`, expanding `mod foo;`
+    /// declarations from its source tree.
+    fn inline_nested_crate(&mut self, name: &str) -> Result<(), Box<dyn std::error::Error>> {
+        if !self.processed_modules.insert(format!("crate:{}", name)) {
+            return Ok(());
+        }
+        let Some(krate) = self.resolver.get_crate(name) else {
+            return Ok(());
+        };
+
+        let root = krate.root.clone();
+        self.source_files.push(krate.lib_file.clone());
+        let content = fs::read_to_string(&krate.lib_file)?;
+        let crate_ast: SynFile = syn::parse_str(&content)?;
+        let items = self.expand_module_items(crate_ast.items, &root)?;
+
+        let ident = Ident::new(name, proc_macro2::Span::call_site());
+        let mut module: Item = syn::parse_quote! {
+            #[allow(dead_code, unused_imports, unused_macros)]
+            pub mod #ident {
+                #(#items)*
+            }
+        };
+        self.cfg.apply_to_item(&mut module);
+        if self.minify.strip_doc_comments {
+            StripDocComments.visit_item_mut(&mut module);
+        }
+        NestCratePaths { name: ident }.visit_item_mut(&mut module);
+        self.bundled_items.push(module);
         Ok(())
     }
 
+    fn expand_module_items(&mut self, items: Vec<Item>, dir: &Path) -> Result<Vec<Item>, Box<dyn std::error::Error>> {
+        let mut expanded = Vec::with_capacity(items.len());
+        for item in items {
+            if !self.cfg.is_enabled(item_attrs(&item)) {
+                continue;
+            }
+            let Item::Mod(mut module) = item else {
+                expanded.push(item);
+                continue;
+            };
+
+            let child_dir = dir.join(module.ident.to_string());
+            let children = match module.content.take() {
+                Some((_, children)) => children,
+                None => {
+                    let candidates = [
+                        dir.join(format!("{}.rs", module.ident)),
+                        child_dir.join("mod.rs"),
+                    ];
+                    let Some(file) = candidates.iter().find(|p| p.is_file()) else {
+                        // Watched while missing, so creating it re-runs the bundle.
+                        self.source_files.extend(candidates.iter().cloned());
+                        return Err(format!("cannot find module '{}' in {}", module.ident, dir.display()).into());
+                    };
+                    self.source_files.push(file.clone());
+                    let content = fs::read_to_string(file)?;
+                    syn::parse_str::<SynFile>(&content)?.items
+                }
+            };
+
+            module.content = Some((Default::default(), self.expand_module_items(children, &child_dir)?));
+            module.semi = None;
+            expanded.push(Item::Mod(module));
+        }
+        Ok(expanded)
+    }
+
     fn process_module_ast(&mut self, ast: &SynFile, _file_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
         for item in &ast.items {
             match item {
                 Item::Use(use_item) => {
                     // Preserve std imports from modules and inline further cp_lib references.
-                    self.process_use_item(use_item)?;
+                    if self.cfg.is_enabled(&use_item.attrs) {
+                        self.process_use_item(use_item)?;
+                    }
                     continue;
                 }
                 Item::Struct(_) | Item::Impl(_) | Item::Fn(_) | Item::Trait(_) | Item::Enum(_) => {
                     // Include all public items from modules
-                    self.inline_cp_lib_paths_in_item(item)?;
-                    let mut cloned = item.clone();
-                    self.rewrite_cp_lib_paths_in_item(&mut cloned);
-                    self.bundled_items.push(cloned);
+                    self.push_item(item)?;
                 }
                 _ => {
                     // Include other items too
-                    self.inline_cp_lib_paths_in_item(item)?;
-                    let mut cloned = item.clone();
-                    self.rewrite_cp_lib_paths_in_item(&mut cloned);
-                    self.bundled_items.push(cloned);
+                    self.push_item(item)?;
                 }
             }
         }
@@ -299,14 +682,15 @@ impl<'a> AstBundler<'a> {
                 .as_secs()));
 
         // Standard library imports
-        output.push_str("use std::io::{self, BufRead, Cursor};\n");
-        output.push_str("use std::collections::*;\n");
-        output.push_str("use std::fmt::Debug;\n\n");
+        for import in HEADER_IMPORTS {
+            output.push_str(&format!("use {};\n", import.strip_suffix("::self").unwrap_or(import)));
+        }
+        output.push('\n');
 
         // Additional use statements found in the code
         for use_stmt in &self.use_statements {
             if !use_stmt.contains("cp_lib") {
-                output.push_str(&use_stmt);
+                output.push_str(use_stmt);
                 output.push('\n');
             }
         }
@@ -315,13 +699,35 @@ impl<'a> AstBundler<'a> {
             output.push('\n');
         }
 
+        let separator = if self.minify.compact { "\n" } else { "\n\n" };
+
         // Bundled items (library code first, then main code)
-        output.push_str("// ==================== Library Code ====================\n");
+        if !self.minify.compact {
+            output.push_str("// ==================== Library Code ====================\n");
+        }
         
+        // Calls to macros that expand to nothing here are dropped; the
+        // definitions stay for calls in expression position.
+        let noop_macros: HashSet<String> = self
+            .bundled_items
+            .iter()
+            .filter_map(|item| match item {
+                Item::Macro(mac) if is_noop_macro(mac) => mac.ident.as_ref().map(|ident| ident.to_string()),
+                _ => None,
+            })
+            .collect();
+        let mut items = self.bundled_items.clone();
+        if !noop_macros.is_empty() {
+            let mut stripper = StripNoopMacroCalls { names: &noop_macros };
+            for item in &mut items {
+                stripper.visit_item_mut(item);
+            }
+        }
+
         let mut main_items = Vec::new();
         let mut lib_items = Vec::new();
         
-        for item in &self.bundled_items {
+        for item in &items {
             match item {
                 Item::Fn(func) if func.sig.ident == "main" => {
                     main_items.push(item);
@@ -339,15 +745,17 @@ impl<'a> AstBundler<'a> {
         // Output library items first
         for item in lib_items {
             output.push_str(&item.to_token_stream().to_string());
-            output.push_str("\n\n");
+            output.push_str(separator);
         }
 
-        output.push_str("// ==================== Main Code ====================\n");
+        if !self.minify.compact {
+            output.push_str("// ==================== Main Code ====================\n");
+        }
         
         // Output main function and other main-file items
         for item in main_items {
             output.push_str(&item.to_token_stream().to_string());
-            output.push_str("\n\n");
+            output.push_str(separator);
         }
 
         Ok(output)
@@ -357,6 +765,7 @@ impl<'a> AstBundler<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::file_resolver::LibCrate;
     use tempfile::tempdir;
     use std::fs;
 
@@ -412,4 +821,123 @@ mod tests {
         assert!(bundled.contains("fn binpow"));
         assert!(!bundled.contains("cp_lib"));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn keeps_std_imports_not_in_header() {
+        let temp_dir = tempdir().unwrap();
+        let resolver = FileResolver::new(temp_dir.path());
+        let mut bundler = AstBundler::new(&resolver);
+
+        let main_src = "use std::io::{self, BufRead, BufReader, Write as _};\n\
+                        use std::{fs::File, collections::HashMap};\n\
+                        fn main() {}";
+        let main_ast: SynFile = syn::parse_str(main_src).unwrap();
+
+        bundler.process_file_ast(&main_ast, Path::new("src/main.rs")).unwrap();
+        let bundled = bundler.generate_bundled_code().unwrap();
+
+        assert!(bundled.contains("use std::io::BufReader;\n"));
+        assert!(bundled.contains("use std::io::Write as _;\n"));
+        assert!(bundled.contains("use std::fs::File;\n"));
+        assert!(bundled.contains("use std::collections::HashMap;\n"));
+        assert_eq!(bundled.matches("use std::io;").count(), 1);
+        assert_eq!(bundled.matches("use std::io::BufRead;").count(), 1);
+    }
+
+    #[test]
+    fn nested_crates_prefix_crate_paths_in_macros() {
+        let temp_dir = tempdir().unwrap();
+        let root = temp_dir.path().join("snippets").join("src");
+        fs::create_dir_all(&root).unwrap();
+        fs::write(
+            root.join("lib.rs"),
+            "pub fn helper() -> i32 { 1 }\n\
+             #[macro_export] macro_rules! call { () => { $crate::helper() }; }\n\
+             pub fn twice() -> i32 { crate::helper() * 2 }\n",
+        )
+        .unwrap();
+
+        let resolver = FileResolver::with_crates(&[LibCrate {
+            mode: CrateMode::Nested,
+            ..LibCrate::new("snippets", &root)
+        }]);
+        let mut bundler = AstBundler::new(&resolver);
+
+        let main_ast: SynFile = syn::parse_str("fn main() { let _ = snippets::twice(); }").unwrap();
+        bundler.process_file_ast(&main_ast, Path::new("src/main.rs")).unwrap();
+        let bundled = bundler.generate_bundled_code().unwrap();
+
+        assert!(bundled.contains("$ crate :: snippets :: helper ()"));
+        assert!(bundled.contains("crate :: snippets :: helper () * 2"));
+        assert!(bundled.contains("snippets :: twice ()"));
+    }
+
+    #[test]
+    fn flattened_crates_shorten_paths_in_exported_macros() {
+        let temp_dir = tempdir().unwrap();
+        let lib_root = temp_dir.path().join("cp-lib").join("src");
+        fs::create_dir_all(lib_root.join("io")).unwrap();
+
+        fs::write(lib_root.join("io").join("mod.rs"), "pub mod scanner;\n").unwrap();
+        fs::write(
+            lib_root.join("io").join("scanner.rs"),
+            "pub trait Readable { fn read() -> Self; }\n\
+             #[macro_export] macro_rules! readable {\n\
+                 ($name:ident) => { impl $crate::io::scanner::Readable for $name { fn read() -> Self { $name } } };\n\
+             }\n",
+        )
+        .unwrap();
+
+        let resolver = FileResolver::new(&lib_root);
+        let mut bundler = AstBundler::new(&resolver);
+
+        let main_src = "use cp_lib::readable;\n\
+                        struct Unit;\n\
+                        readable!(Unit);\n\
+                        fn main() { let _ = <Unit as cp_lib::io::scanner::Readable>::read(); }";
+        let main_ast: SynFile = syn::parse_str(main_src).unwrap();
+
+        bundler.process_file_ast(&main_ast, Path::new("src/main.rs")).unwrap();
+        let bundled = bundler.generate_bundled_code().unwrap();
+
+        assert!(bundled.contains("pub trait Readable"));
+        assert!(bundled.contains("$ crate :: Readable for $ name"));
+        assert!(!bundled.contains("scanner"));
+        assert!(!bundled.contains("cp_lib"));
+    }
+
+    #[test]
+    fn calls_to_noop_macros_are_dropped() {
+        let temp_dir = tempdir().unwrap();
+        let lib_root = temp_dir.path().join("cp-lib").join("src");
+        fs::create_dir_all(&lib_root).unwrap();
+
+        fs::write(
+            lib_root.join("debug.rs"),
+            "#[cfg(debug_assertions)] pub fn format_entries() -> String { String::new() }\n\
+             #[cfg(debug_assertions)] #[macro_export] macro_rules! debug {\n\
+                 ($($value:expr),*) => { eprintln!(\"{}\", $crate::debug::format_entries()) };\n\
+             }\n\
+             #[cfg(not(debug_assertions))] #[macro_export] macro_rules! debug {\n\
+                 ($($tokens:tt)*) => {};\n\
+             }\n",
+        )
+        .unwrap();
+
+        let resolver = FileResolver::new(&lib_root);
+        let mut bundler = AstBundler::new(&resolver);
+
+        let main_src = "use cp_lib::debug;\n\
+                        fn main() { let x = expensive(); debug!(x, x + 1); if x > 0 { debug!(x); } println!(\"{}\", x); }";
+        let main_ast: SynFile = syn::parse_str(main_src).unwrap();
+
+        bundler.process_file_ast(&main_ast, Path::new("src/main.rs")).unwrap();
+        let bundled = bundler.generate_bundled_code().unwrap();
+
+        assert!(bundled.contains("macro_rules ! debug"));
+        assert!(!bundled.contains("debug ! (x"));
+        assert!(!bundled.contains("eprintln"));
+        assert!(!bundled.contains("format_entries"));
+        assert!(bundled.contains("println !"));
+    }
+}
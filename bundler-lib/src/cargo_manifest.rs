@@ -0,0 +1,237 @@
+/*
+ * Discovery of bundleable crates from Cargo.toml path dependencies
+ */
+
+use crate::file_resolver::LibCrate;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use syn::visit::Visit;
+use toml::Value;
+
+pub struct CargoManifest {
+    path: PathBuf,
+    manifest: Value,
+}
+
+impl CargoManifest {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let content = fs::read_to_string(path)?;
+        let manifest = content
+            .parse::<Value>()
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            manifest,
+        })
+    }
+
+    /// Finds the nearest `Cargo.toml` above a source file.
+    pub fn discover(source_file: &Path) -> Option<PathBuf> {
+        source_file
+            .ancestors()
+            .skip(1)
+            .map(|dir| dir.join("Cargo.toml"))
+            .find(|candidate| candidate.is_file())
+    }
+
+    fn dir(&self) -> &Path {
+        self.path.parent().unwrap_or(Path::new(""))
+    }
+
+    /// The `[dependencies]` entries with a `path` that are in `referenced`,
+    /// named as they are written in code.
+    ///
+    /// A renamed dependency (`foo = { path = "..", package = "bar" }`) is used as
+    /// `foo`; otherwise the dependency's `[lib] name` (or package name) applies.
+    /// A dependency whose manifest cannot be read is only an error when it is
+    /// referenced; otherwise it is skipped with a warning in `skipped`.
+    pub fn path_dependencies(&self, referenced: &HashSet<String>) -> Result<PathDependencies, Box<dyn std::error::Error>> {
+        let mut found = PathDependencies::default();
+        let Some(deps) = self.manifest.get("dependencies").and_then(Value::as_table) else {
+            return Ok(found);
+        };
+
+        for (key, spec) in deps {
+            let Some(dep_path) = spec.get("path").and_then(Value::as_str) else {
+                continue;
+            };
+            let dep_dir = self.dir().join(dep_path);
+            let dep_manifest = match Self::load(&dep_dir.join("Cargo.toml")) {
+                Ok(manifest) => manifest,
+                // The lib name is unknown without the manifest, so go by the key.
+                Err(e) if referenced.contains(&key.replace('-', "_")) => return Err(e),
+                Err(e) => {
+                    found.skipped.push(format!("Skipping path dependency '{}': {}", key, e));
+                    continue;
+                }
+            };
+            let lib = dep_manifest.manifest.get("lib");
+
+            let name = if spec.get("package").is_some() {
+                key.clone()
+            } else {
+                lib.and_then(|l| l.get("name"))
+                    .or_else(|| dep_manifest.manifest.get("package").and_then(|p| p.get("name")))
+                    .and_then(Value::as_str)
+                    .unwrap_or(key)
+                    .to_string()
+            };
+
+            let lib_file = dep_dir.join(
+                lib.and_then(|l| l.get("path"))
+                    .and_then(Value::as_str)
+                    .unwrap_or("src/lib.rs"),
+            );
+            let root = lib_file.parent().unwrap_or(&dep_dir).to_path_buf();
+
+            let name = name.replace('-', "_");
+            if !referenced.contains(&name) {
+                continue;
+            }
+            found.crates.push(LibCrate {
+                lib_file,
+                ..LibCrate::new(&name, &root)
+            });
+        }
+        Ok(found)
+    }
+}
+
+/// Result of `CargoManifest::path_dependencies`.
+#[derive(Debug, Default)]
+pub struct PathDependencies {
+    pub crates: Vec<LibCrate>,
+    /// Warnings for unreferenced dependencies whose manifest could not be read.
+    pub skipped: Vec<String>,
+}
+
+/// Names of the crates a source file refers to by leading path segment.
+pub fn referenced_crates(ast: &syn::File) -> HashSet<String> {
+    struct Collector(HashSet<String>);
+
+    impl<'ast> Visit<'ast> for Collector {
+        fn visit_path(&mut self, path: &'ast syn::Path) {
+            if path.segments.len() > 1 {
+                self.0.insert(path.segments[0].ident.to_string());
+            }
+            syn::visit::visit_path(self, path);
+        }
+
+        fn visit_use_tree(&mut self, tree: &'ast syn::UseTree) {
+            // Only the leading segment names a crate, so nested trees are not visited.
+            match tree {
+                syn::UseTree::Path(p) => {
+                    self.0.insert(p.ident.to_string());
+                }
+                syn::UseTree::Name(n) => {
+                    self.0.insert(n.ident.to_string());
+                }
+                syn::UseTree::Rename(r) => {
+                    self.0.insert(r.ident.to_string());
+                }
+                syn::UseTree::Group(g) => {
+                    for item in &g.items {
+                        self.visit_use_tree(item);
+                    }
+                }
+                syn::UseTree::Glob(_) => {}
+            }
+        }
+    }
+
+    let mut collector = Collector(HashSet::new());
+    collector.visit_file(ast);
+    collector.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn reads_path_dependencies_with_renames_and_lib_paths() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+
+        fs::write(
+            root.join("Cargo.toml"),
+            r#"
+            [package]
+            name = "contest"
+
+            [dependencies]
+            regex = "1"
+            team = { path = "libs/team-lib", package = "team-lib" }
+
+            [dependencies.my-snippets]
+            path = "libs/snippets"
+            "#,
+        )
+        .unwrap();
+
+        fs::create_dir_all(root.join("libs/team-lib/src")).unwrap();
+        fs::write(root.join("libs/team-lib/Cargo.toml"), "[package]\nname = \"team-lib\"\n").unwrap();
+
+        fs::create_dir_all(root.join("libs/snippets/code")).unwrap();
+        fs::write(
+            root.join("libs/snippets/Cargo.toml"),
+            "[package]\nname = \"my-snippets\"\n\n[lib]\npath = \"code/root.rs\"\n",
+        )
+        .unwrap();
+
+        let manifest = CargoManifest::load(&root.join("Cargo.toml")).unwrap();
+        let referenced = HashSet::from(["team".to_string(), "my_snippets".to_string()]);
+        let mut crates = manifest.path_dependencies(&referenced).unwrap().crates;
+        crates.sort_by(|a, b| a.name.cmp(&b.name));
+
+        assert_eq!(crates.len(), 2);
+        assert_eq!(crates[0].name, "my_snippets");
+        assert_eq!(crates[0].root, root.join("libs/snippets/code"));
+        assert_eq!(crates[0].lib_file, root.join("libs/snippets/code/root.rs"));
+        assert_eq!(crates[1].name, "team");
+        assert_eq!(crates[1].root, root.join("libs/team-lib/src"));
+    }
+
+    #[test]
+    fn broken_manifests_only_fail_referenced_dependencies() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+
+        fs::write(
+            root.join("Cargo.toml"),
+            "[package]\nname = \"contest\"\n\n[dependencies]\n\
+             snippets = { path = \"libs/snippets\" }\n\
+             old-lib = { path = \"libs/missing\" }\n",
+        )
+        .unwrap();
+        fs::create_dir_all(root.join("libs/snippets/src")).unwrap();
+        fs::write(root.join("libs/snippets/Cargo.toml"), "[package]\nname = \"snippets\"\n").unwrap();
+
+        let manifest = CargoManifest::load(&root.join("Cargo.toml")).unwrap();
+        let found = manifest.path_dependencies(&HashSet::from(["snippets".to_string()])).unwrap();
+        assert_eq!(found.crates.len(), 1);
+        assert_eq!(found.crates[0].name, "snippets");
+        assert_eq!(found.skipped.len(), 1);
+        assert!(found.skipped[0].starts_with("Skipping path dependency 'old-lib'"), "{}", found.skipped[0]);
+
+        assert!(manifest.path_dependencies(&HashSet::from(["old_lib".to_string()])).is_err());
+    }
+
+    #[test]
+    fn collects_referenced_crate_names() {
+        let ast: syn::File = syn::parse_str(
+            "use cp_lib::io::scanner::Scanner;\n\
+             use {snippets::dsu, std::io};\n\
+             fn main() { let _ = other::f(); let x = 1; }",
+        )
+        .unwrap();
+        let names = referenced_crates(&ast);
+
+        assert!(names.contains("cp_lib"));
+        assert!(names.contains("snippets"));
+        assert!(names.contains("other"));
+        assert!(!names.contains("bundler_lib"));
+    }
+}
@@ -0,0 +1,353 @@
+/*
+ * Evaluation of #[cfg(...)] attributes against the judge environment
+ */
+
+use std::collections::HashSet;
+use syn::punctuated::Punctuated;
+use syn::visit_mut::VisitMut;
+use syn::{Attribute, Expr, ExprLit, Item, Lit, Meta, Stmt, Token};
+
+/// The set of cfg options enabled when the bundle is compiled on the judge.
+///
+/// `test` and `debug_assertions` are always off, `feature = "..."` is on only
+/// when listed. Any other name that is not listed (e.g. `target_os`) is left
+/// for rustc to decide, so the attribute is kept in the bundle.
+#[derive(Debug, Clone, Default)]
+pub struct CfgSet {
+    flags: HashSet<String>,
+    key_values: HashSet<(String, String)>,
+}
+
+impl CfgSet {
+    /// Builds the set from entries like `"local"` or `"feature = \"fast\""`.
+    pub fn new<S: AsRef<str>>(entries: &[S]) -> Self {
+        let mut set = Self::default();
+        for entry in entries {
+            let entry = entry.as_ref().trim();
+            match entry.split_once('=') {
+                Some((key, value)) => {
+                    let value = value.trim().trim_matches('"');
+                    set.key_values
+                        .insert((key.trim().to_string(), value.to_string()));
+                }
+                None => {
+                    set.flags.insert(entry.to_string());
+                }
+            }
+        }
+        set
+    }
+
+    /// Evaluates a cfg predicate. Returns `None` when the outcome depends on
+    /// something only rustc knows.
+    pub fn evaluate(&self, meta: &Meta) -> Option<bool> {
+        match meta {
+            Meta::Path(path) => {
+                let ident = path.get_ident()?.to_string();
+                if self.flags.contains(&ident) {
+                    Some(true)
+                } else if ident == "test" || ident == "debug_assertions" {
+                    Some(false)
+                } else {
+                    None
+                }
+            }
+            Meta::NameValue(nv) => {
+                let key = nv.path.get_ident()?.to_string();
+                let Expr::Lit(ExprLit {
+                    lit: Lit::Str(value),
+                    ..
+                }) = &nv.value
+                else {
+                    return None;
+                };
+                if self.key_values.contains(&(key.clone(), value.value())) {
+                    Some(true)
+                } else if key == "feature" {
+                    Some(false)
+                } else {
+                    None
+                }
+            }
+            Meta::List(list) => {
+                let nested = list
+                    .parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
+                    .ok()?;
+                let results: Vec<Option<bool>> =
+                    nested.iter().map(|m| self.evaluate(m)).collect();
+                let name = list.path.get_ident()?.to_string();
+                match name.as_str() {
+                    "all" => {
+                        if results.contains(&Some(false)) {
+                            Some(false)
+                        } else if results.iter().all(|r| *r == Some(true)) {
+                            Some(true)
+                        } else {
+                            None
+                        }
+                    }
+                    "any" => {
+                        if results.contains(&Some(true)) {
+                            Some(true)
+                        } else if results.iter().all(|r| *r == Some(false)) {
+                            Some(false)
+                        } else {
+                            None
+                        }
+                    }
+                    "not" if results.len() == 1 => results[0].map(|r| !r),
+                    _ => None,
+                }
+            }
+        }
+    }
+
+    fn evaluate_attr(&self, attr: &Attribute) -> Option<bool> {
+        if !attr.path().is_ident("cfg") {
+            return None;
+        }
+        let meta = attr.parse_args::<Meta>().ok()?;
+        self.evaluate(&meta)
+    }
+
+    /// False if any `#[cfg]` on the node is known to be disabled on the judge.
+    pub fn is_enabled(&self, attrs: &[Attribute]) -> bool {
+        !attrs.iter().any(|a| self.evaluate_attr(a) == Some(false))
+    }
+
+    /// Removes `#[cfg]` attributes that are known to hold, since the judge
+    /// compiles the bundle without our flags.
+    pub fn strip_resolved(&self, attrs: &mut Vec<Attribute>) {
+        attrs.retain(|a| self.evaluate_attr(a) != Some(true));
+    }
+
+    /// Drops disabled nodes nested inside `item` and strips resolved cfgs.
+    pub fn apply_to_item(&self, item: &mut Item) {
+        CfgStripper { cfg: self }.visit_item_mut(item);
+    }
+}
+
+pub fn item_attrs(item: &Item) -> &[Attribute] {
+    match item {
+        Item::Const(i) => &i.attrs,
+        Item::Enum(i) => &i.attrs,
+        Item::ExternCrate(i) => &i.attrs,
+        Item::Fn(i) => &i.attrs,
+        Item::ForeignMod(i) => &i.attrs,
+        Item::Impl(i) => &i.attrs,
+        Item::Macro(i) => &i.attrs,
+        Item::Mod(i) => &i.attrs,
+        Item::Static(i) => &i.attrs,
+        Item::Struct(i) => &i.attrs,
+        Item::Trait(i) => &i.attrs,
+        Item::TraitAlias(i) => &i.attrs,
+        Item::Type(i) => &i.attrs,
+        Item::Union(i) => &i.attrs,
+        Item::Use(i) => &i.attrs,
+        _ => &[],
+    }
+}
+
+fn item_attrs_mut(item: &mut Item) -> Option<&mut Vec<Attribute>> {
+    match item {
+        Item::Const(i) => Some(&mut i.attrs),
+        Item::Enum(i) => Some(&mut i.attrs),
+        Item::ExternCrate(i) => Some(&mut i.attrs),
+        Item::Fn(i) => Some(&mut i.attrs),
+        Item::ForeignMod(i) => Some(&mut i.attrs),
+        Item::Impl(i) => Some(&mut i.attrs),
+        Item::Macro(i) => Some(&mut i.attrs),
+        Item::Mod(i) => Some(&mut i.attrs),
+        Item::Static(i) => Some(&mut i.attrs),
+        Item::Struct(i) => Some(&mut i.attrs),
+        Item::Trait(i) => Some(&mut i.attrs),
+        Item::TraitAlias(i) => Some(&mut i.attrs),
+        Item::Type(i) => Some(&mut i.attrs),
+        Item::Union(i) => Some(&mut i.attrs),
+        Item::Use(i) => Some(&mut i.attrs),
+        _ => None,
+    }
+}
+
+fn stmt_attrs(stmt: &Stmt) -> &[Attribute] {
+    match stmt {
+        Stmt::Local(local) => &local.attrs,
+        Stmt::Item(item) => item_attrs(item),
+        Stmt::Macro(mac) => &mac.attrs,
+        Stmt::Expr(expr, _) => expr_attrs(expr),
+    }
+}
+
+/// Attributes of expressions that are commonly put under `#[cfg]` as
+/// statements, such as blocks, calls and loops.
+fn expr_attrs(expr: &Expr) -> &[Attribute] {
+    match expr {
+        Expr::Assign(e) => &e.attrs,
+        Expr::Block(e) => &e.attrs,
+        Expr::Call(e) => &e.attrs,
+        Expr::ForLoop(e) => &e.attrs,
+        Expr::If(e) => &e.attrs,
+        Expr::Loop(e) => &e.attrs,
+        Expr::Macro(e) => &e.attrs,
+        Expr::MethodCall(e) => &e.attrs,
+        Expr::While(e) => &e.attrs,
+        _ => &[],
+    }
+}
+
+fn expr_attrs_mut(expr: &mut Expr) -> Option<&mut Vec<Attribute>> {
+    match expr {
+        Expr::Assign(e) => Some(&mut e.attrs),
+        Expr::Block(e) => Some(&mut e.attrs),
+        Expr::Call(e) => Some(&mut e.attrs),
+        Expr::ForLoop(e) => Some(&mut e.attrs),
+        Expr::If(e) => Some(&mut e.attrs),
+        Expr::Loop(e) => Some(&mut e.attrs),
+        Expr::Macro(e) => Some(&mut e.attrs),
+        Expr::MethodCall(e) => Some(&mut e.attrs),
+        Expr::While(e) => Some(&mut e.attrs),
+        _ => None,
+    }
+}
+
+struct CfgStripper<'a> {
+    cfg: &'a CfgSet,
+}
+
+impl VisitMut for CfgStripper<'_> {
+    fn visit_item_mut(&mut self, item: &mut Item) {
+        if let Some(attrs) = item_attrs_mut(item) {
+            self.cfg.strip_resolved(attrs);
+        }
+        syn::visit_mut::visit_item_mut(self, item);
+    }
+
+    fn visit_item_mod_mut(&mut self, module: &mut syn::ItemMod) {
+        if let Some((_, items)) = &mut module.content {
+            items.retain(|item| self.cfg.is_enabled(item_attrs(item)));
+        }
+        syn::visit_mut::visit_item_mod_mut(self, module);
+    }
+
+    fn visit_item_impl_mut(&mut self, item: &mut syn::ItemImpl) {
+        item.items.retain(|impl_item| match impl_item {
+            syn::ImplItem::Const(c) => self.cfg.is_enabled(&c.attrs),
+            syn::ImplItem::Fn(f) => self.cfg.is_enabled(&f.attrs),
+            syn::ImplItem::Type(t) => self.cfg.is_enabled(&t.attrs),
+            syn::ImplItem::Macro(m) => self.cfg.is_enabled(&m.attrs),
+            _ => true,
+        });
+        syn::visit_mut::visit_item_impl_mut(self, item);
+    }
+
+    fn visit_impl_item_fn_mut(&mut self, f: &mut syn::ImplItemFn) {
+        self.cfg.strip_resolved(&mut f.attrs);
+        syn::visit_mut::visit_impl_item_fn_mut(self, f);
+    }
+
+    fn visit_block_mut(&mut self, block: &mut syn::Block) {
+        block.stmts.retain(|stmt| self.cfg.is_enabled(stmt_attrs(stmt)));
+        syn::visit_mut::visit_block_mut(self, block);
+    }
+
+    fn visit_stmt_mut(&mut self, stmt: &mut Stmt) {
+        if let Stmt::Expr(expr, _) = stmt {
+            if let Some(attrs) = expr_attrs_mut(expr) {
+                self.cfg.strip_resolved(attrs);
+            }
+        }
+        syn::visit_mut::visit_stmt_mut(self, stmt);
+    }
+
+    fn visit_local_mut(&mut self, local: &mut syn::Local) {
+        self.cfg.strip_resolved(&mut local.attrs);
+        syn::visit_mut::visit_local_mut(self, local);
+    }
+
+    fn visit_stmt_macro_mut(&mut self, mac: &mut syn::StmtMacro) {
+        self.cfg.strip_resolved(&mut mac.attrs);
+        syn::visit_mut::visit_stmt_macro_mut(self, mac);
+    }
+
+    fn visit_fields_named_mut(&mut self, fields: &mut syn::FieldsNamed) {
+        fields.named = std::mem::take(&mut fields.named)
+            .into_pairs()
+            .filter(|pair| self.cfg.is_enabled(&pair.value().attrs))
+            .collect();
+        syn::visit_mut::visit_fields_named_mut(self, fields);
+    }
+
+    fn visit_field_mut(&mut self, field: &mut syn::Field) {
+        self.cfg.strip_resolved(&mut field.attrs);
+        syn::visit_mut::visit_field_mut(self, field);
+    }
+
+    fn visit_expr_match_mut(&mut self, expr: &mut syn::ExprMatch) {
+        expr.arms.retain(|arm| self.cfg.is_enabled(&arm.attrs));
+        syn::visit_mut::visit_expr_match_mut(self, expr);
+    }
+
+    fn visit_arm_mut(&mut self, arm: &mut syn::Arm) {
+        self.cfg.strip_resolved(&mut arm.attrs);
+        syn::visit_mut::visit_arm_mut(self, arm);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quote::ToTokens;
+
+    fn eval(set: &CfgSet, predicate: &str) -> Option<bool> {
+        set.evaluate(&syn::parse_str::<Meta>(predicate).unwrap())
+    }
+
+    #[test]
+    fn evaluates_predicates() {
+        let set = CfgSet::new(&["local", "feature = \"fast\""]);
+
+        assert_eq!(eval(&set, "local"), Some(true));
+        assert_eq!(eval(&set, "test"), Some(false));
+        assert_eq!(eval(&set, "debug_assertions"), Some(false));
+        assert_eq!(eval(&set, "feature = \"fast\""), Some(true));
+        assert_eq!(eval(&set, "feature = \"slow\""), Some(false));
+        assert_eq!(eval(&set, "target_os = \"linux\""), None);
+        assert_eq!(eval(&set, "not(test)"), Some(true));
+        assert_eq!(eval(&set, "all(local, test)"), Some(false));
+        assert_eq!(eval(&set, "all(local, unix)"), None);
+        assert_eq!(eval(&set, "any(test, unix)"), None);
+        assert_eq!(eval(&set, "any(test, local)"), Some(true));
+    }
+
+    #[test]
+    fn strips_disabled_nested_items() {
+        let set = CfgSet::new(&["local"]);
+        let mut item: Item = syn::parse_str(
+            "fn main() {
+                #[cfg(test)]
+                let a = 1;
+                #[cfg(local)]
+                let b = 2;
+                #[cfg(unix)]
+                let c = 3;
+                #[cfg(debug_assertions)]
+                {
+                    eprintln!(\"{}\", a);
+                }
+                #[cfg(local)]
+                for _ in 0..b {}
+            }",
+        )
+        .unwrap();
+
+        set.apply_to_item(&mut item);
+        let code = item.to_token_stream().to_string();
+
+        assert!(!code.contains("let a"));
+        assert!(code.contains("let b"));
+        assert!(!code.contains("cfg (local)"));
+        assert!(code.contains("# [cfg (unix)] let c"));
+        assert!(!code.contains("eprintln"));
+        assert!(code.contains("for _ in 0 .. b"));
+    }
+}
@@ -0,0 +1,254 @@
+/*
+ * Output checkers: built-in comparisons and testlib-style checker programs
+ */
+
+use crate::compare::{self, Mismatch};
+use crate::limits::Limits;
+use crate::runner;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Decides whether an output is accepted.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Checker {
+    /// Token-wise comparison; real-valued expected tokens within the tolerance.
+    Tokens { tolerance: f64 },
+    /// Every token is a number, accepted within `abs` or `rel` error.
+    Float { abs: f64, rel: f64 },
+    /// The same tokens in any order.
+    Unordered,
+    /// Like `Tokens`, but `yes`/`no` match regardless of case.
+    YesNo,
+    /// A compiled checker called as `checker <input> <expected> <actual>`.
+    ///
+    /// Exit code 0 accepts, 1 (WA) and 2 (PE) reject; the first line of its
+    /// stderr, or else stdout, is the message. Any other exit code means the
+    /// checker itself failed, as does running past `PROGRAM_TIME_LIMIT`.
+    Program(PathBuf),
+}
+
+impl Default for Checker {
+    fn default() -> Self {
+        Checker::Tokens { tolerance: 1e-6 }
+    }
+}
+
+/// A checker as named in `cp-rs.toml` or on the command line: a built-in name
+/// or a Rust source file that still has to be bundled and compiled.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CheckerSpec {
+    Builtin(String),
+    Source(PathBuf),
+}
+
+impl CheckerSpec {
+    pub const BUILTINS: [&'static str; 4] = ["tokens", "float", "unordered", "yesno"];
+
+    pub fn parse(spec: &str) -> Self {
+        if Self::BUILTINS.contains(&spec) {
+            CheckerSpec::Builtin(spec.to_string())
+        } else {
+            CheckerSpec::Source(PathBuf::from(spec))
+        }
+    }
+}
+
+impl Checker {
+    /// CPU time a checker program gets for one output.
+    pub const PROGRAM_TIME_LIMIT: Duration = Duration::from_secs(5);
+
+    /// Parses a built-in checker name: `tokens`, `float`, `unordered` or `yesno`.
+    pub fn builtin(name: &str, tolerance: f64) -> Option<Self> {
+        match name {
+            "tokens" => Some(Checker::Tokens { tolerance }),
+            "float" => Some(Checker::Float {
+                abs: tolerance,
+                rel: tolerance,
+            }),
+            "unordered" => Some(Checker::Unordered),
+            "yesno" => Some(Checker::YesNo),
+            _ => None,
+        }
+    }
+
+    /// `Ok(Err(message))` is a rejected output, `Err` a checker that could not run.
+    pub fn check(&self, input: &str, expected: &str, actual: &str) -> io::Result<Result<(), String>> {
+        let mismatch = |m: Mismatch| m.to_string();
+        Ok(match self {
+            Checker::Tokens { tolerance } => {
+                compare::compare_outputs(expected, actual, *tolerance).map_err(mismatch)
+            }
+            Checker::Float { abs, rel } => compare::compare_by(expected, actual, |e, a| {
+                match (e.parse::<f64>(), a.parse::<f64>()) {
+                    (Ok(e), Ok(a)) => {
+                        let diff = (e - a).abs();
+                        diff <= *abs || diff <= rel * e.abs()
+                    }
+                    _ => false,
+                }
+            })
+            .map_err(mismatch),
+            Checker::Unordered => check_unordered(expected, actual),
+            Checker::YesNo => compare::compare_by(expected, actual, |e, a| {
+                let is_yes_no = |t: &str| t.eq_ignore_ascii_case("yes") || t.eq_ignore_ascii_case("no");
+                e == a || (is_yes_no(e) && e.eq_ignore_ascii_case(a))
+            })
+            .map_err(mismatch),
+            Checker::Program(binary) => {
+                return run_program(binary, input, expected, actual, Self::PROGRAM_TIME_LIMIT)
+            }
+        })
+    }
+}
+
+fn check_unordered(expected: &str, actual: &str) -> Result<(), String> {
+    let sorted = |text| {
+        let mut tokens: Vec<&str> = compare::tokens(text).map(|(_, t)| t).collect();
+        tokens.sort_unstable();
+        tokens
+    };
+    let (expected, actual) = (sorted(expected), sorted(actual));
+    if expected.len() != actual.len() {
+        return Err(format!("expected {} tokens, found {}", expected.len(), actual.len()));
+    }
+    match expected.iter().zip(&actual).find(|(e, a)| e != a) {
+        None => Ok(()),
+        Some(_) => {
+            let missing = expected.iter().find(|t| !actual.contains(t));
+            let extra = actual.iter().find(|t| !expected.contains(t));
+            Err(match (missing, extra) {
+                (Some(m), Some(x)) => format!("missing `{}`, unexpected `{}`", m, x),
+                _ => "token counts differ".to_string(),
+            })
+        }
+    }
+}
+
+fn run_program(
+    binary: &Path,
+    input: &str,
+    expected: &str,
+    actual: &str,
+    time_limit: Duration,
+) -> io::Result<Result<(), String>> {
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+    let dir = std::env::temp_dir().join(format!(
+        "cp-rs-checker-{}-{}",
+        std::process::id(),
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    ));
+    fs::create_dir_all(&dir)?;
+    let files = [("input.txt", input), ("expected.txt", expected), ("actual.txt", actual)]
+        .map(|(name, content)| (dir.join(name), content));
+    for (path, content) in &files {
+        fs::write(path, content)?;
+    }
+
+    let args: Vec<String> = files.iter().map(|(path, _)| path.to_string_lossy().into_owned()).collect();
+    let limits = Limits {
+        time: Some(time_limit),
+        memory: None,
+    };
+    let output = runner::run_limited(binary, &args, &[], &limits);
+    let _ = fs::remove_dir_all(&dir);
+    let output = output?;
+    if output.timed_out() {
+        return Err(io::Error::other(format!(
+            "checker timed out after {:.1}s",
+            output.elapsed.as_secs_f64()
+        )));
+    }
+
+    let message = [&output.stderr, &output.stdout]
+        .iter()
+        .map(|text| text.lines().next().unwrap_or("").trim().to_string())
+        .find(|line| !line.is_empty())
+        .unwrap_or_default();
+    match output.status.code() {
+        Some(0) => Ok(Ok(())),
+        Some(1) | Some(2) => Ok(Err(message)),
+        _ => Err(io::Error::other(format!("checker failed ({}): {}", output.status, message))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project_config::JudgeProfile;
+    use tempfile::tempdir;
+
+    #[test]
+    fn parses_checker_specs() {
+        assert_eq!(CheckerSpec::parse("yesno"), CheckerSpec::Builtin("yesno".to_string()));
+        assert_eq!(
+            CheckerSpec::parse("tests/1900A/checker.rs"),
+            CheckerSpec::Source(PathBuf::from("tests/1900A/checker.rs"))
+        );
+        for name in CheckerSpec::BUILTINS {
+            assert!(Checker::builtin(name, 1e-6).is_some());
+        }
+    }
+
+    #[test]
+    fn float_checker_uses_absolute_or_relative_error() {
+        let checker = Checker::Float { abs: 1e-6, rel: 1e-9 };
+        assert!(checker.check("", "10", "10.0000001").unwrap().is_ok());
+        assert!(checker.check("", "1e12", "1000000000500").unwrap().is_ok());
+        assert!(checker.check("", "1.5", "1.6").unwrap().is_err());
+        assert!(checker.check("", "1.5", "abc").unwrap().is_err());
+    }
+
+    #[test]
+    fn unordered_and_yes_no_checkers() {
+        assert!(Checker::Unordered.check("", "1 2 3\n", "3\n1 2").unwrap().is_ok());
+        assert_eq!(
+            Checker::Unordered.check("", "1 2 3", "1 2 4").unwrap(),
+            Err("missing `3`, unexpected `4`".to_string())
+        );
+        assert!(Checker::Unordered.check("", "1 2", "1 2 2").unwrap().is_err());
+
+        assert!(Checker::YesNo.check("", "YES\nNo\n3", "yes\nNO\n3").unwrap().is_ok());
+        assert!(Checker::YesNo.check("", "Alice", "alice").unwrap().is_err());
+    }
+
+    #[test]
+    fn runs_checker_program() {
+        let dir = tempdir().unwrap();
+        let source = dir.path().join("checker.rs");
+        // Accepts any pair of numbers summing to the value in the input.
+        fs::write(
+            &source,
+            "fn main() {\n\
+                 let args: Vec<String> = std::env::args().collect();\n\
+                 let read = |i: usize| std::fs::read_to_string(&args[i]).unwrap();\n\
+                 let n: i64 = read(1).trim().parse().unwrap();\n\
+                 let v: Vec<i64> = read(3).split_whitespace().map(|t| t.parse().unwrap()).collect();\n\
+                 if v.len() == 2 && v[0] + v[1] == n { std::process::exit(0); }\n\
+                 eprintln!(\"sum is not {}\", n);\n\
+                 std::process::exit(1);\n\
+             }\n",
+        )
+        .unwrap();
+        let binary = dir.path().join("checker");
+        runner::compile(&source, &binary, &JudgeProfile::default()).unwrap();
+
+        let checker = Checker::Program(binary);
+        assert!(checker.check("5\n", "2 3\n", "4 1\n").unwrap().is_ok());
+        assert_eq!(checker.check("5\n", "2 3\n", "4 4\n").unwrap(), Err("sum is not 5".to_string()));
+    }
+
+    #[test]
+    fn reports_a_checker_program_that_never_returns() {
+        let dir = tempdir().unwrap();
+        let source = dir.path().join("checker.rs");
+        fs::write(&source, "fn main() { loop { std::hint::black_box(0); } }\n").unwrap();
+        let binary = dir.path().join("checker");
+        runner::compile(&source, &binary, &JudgeProfile::default()).unwrap();
+
+        let err = run_program(&binary, "1\n", "1\n", "1\n", Duration::from_millis(100)).unwrap_err();
+        assert!(err.to_string().starts_with("checker timed out"), "{}", err);
+    }
+}
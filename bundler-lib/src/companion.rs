@@ -0,0 +1,191 @@
+/*
+ * Competitive Companion listener: problems posted by the browser extension
+ */
+
+use crate::limits::Limits;
+use crate::samples::{self, Sample};
+use serde::Deserialize;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::time::Duration;
+
+/// One of the ports Competitive Companion posts to out of the box.
+pub const DEFAULT_PORT: u16 = 10043;
+
+/// Problem as sent by Competitive Companion; fields not needed here are ignored.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Task {
+    pub name: String,
+    #[serde(default)]
+    pub group: String,
+    #[serde(default)]
+    pub url: String,
+    #[serde(default)]
+    pub interactive: bool,
+    /// Milliseconds.
+    pub time_limit: Option<u64>,
+    /// Megabytes.
+    pub memory_limit: Option<u64>,
+    #[serde(default)]
+    pub tests: Vec<Sample>,
+}
+
+impl Task {
+    /// ID taken from the problem URL as for saved pages, or else the name
+    /// with everything but letters and digits replaced by `_`.
+    pub fn id(&self) -> String {
+        samples::id_from_url(&self.url).unwrap_or_else(|| {
+            let id: String = self
+                .name
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+                .collect();
+            let id = id.split('_').filter(|part| !part.is_empty()).collect::<Vec<_>>().join("_");
+            if id.is_empty() { "problem".to_string() } else { id }
+        })
+    }
+
+    pub fn limits(&self) -> Limits {
+        Limits {
+            time: self.time_limit.map(Duration::from_millis),
+            memory: self.memory_limit.map(|mb| mb * 1024 * 1024),
+        }
+    }
+}
+
+/// Largest request body accepted; tasks with many samples are well below it.
+pub const MAX_BODY_SIZE: usize = 16 << 20;
+
+/// Reads one HTTP request from `stream` and parses its body as a task,
+/// answering `200 OK`, or `400`/`405` when the request is not a task.
+pub fn receive(mut stream: impl Read + Write) -> io::Result<Task> {
+    let result = read_task(&mut stream);
+    let status = match &result {
+        Ok(_) => "200 OK",
+        Err(e) if e.kind() == io::ErrorKind::Unsupported => "405 Method Not Allowed",
+        Err(_) => "400 Bad Request",
+    };
+    write!(stream, "HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", status)?;
+    stream.flush()?;
+    result
+}
+
+fn read_task(stream: impl Read) -> io::Result<Task> {
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    if !request_line.starts_with("POST ") {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("expected a POST request, got `{}`", request_line.trim_end()),
+        ));
+    }
+
+    let mut content_length = 0;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim_end().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = value
+                    .trim()
+                    .parse()
+                    .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "invalid Content-Length"))?;
+            }
+        }
+    }
+
+    if content_length > MAX_BODY_SIZE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("request body of {} bytes exceeds the {} byte limit", content_length, MAX_BODY_SIZE),
+        ));
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+    serde_json::from_slice(&body).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Waits for the next connection on `listener` and receives a task from it.
+pub fn accept(listener: &TcpListener) -> io::Result<Task> {
+    let (stream, _) = listener.accept()?;
+    stream.set_read_timeout(Some(Duration::from_secs(10)))?;
+    receive(stream)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpStream;
+    use std::thread;
+
+    const FIXTURE: &str = include_str!("../tests/fixtures/companion_1900A.json");
+
+    fn post(port: u16, request: String) -> thread::JoinHandle<String> {
+        thread::spawn(move || {
+            let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+            stream.write_all(request.as_bytes()).unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        })
+    }
+
+    #[test]
+    fn receives_posted_task() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let client = post(
+            port,
+            format!(
+                "POST / HTTP/1.1\r\nHost: localhost:{}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                port,
+                FIXTURE.len(),
+                FIXTURE
+            ),
+        );
+
+        let task = accept(&listener).unwrap();
+        assert!(client.join().unwrap().starts_with("HTTP/1.1 200 OK\r\n"));
+        assert_eq!(task.id(), "1900A");
+        assert_eq!(task.name, "A. Cover in Water");
+        assert_eq!(task.tests.len(), 1);
+        assert_eq!(task.tests[0].output, "2\n2\n5\n0\n2\n");
+        assert_eq!(
+            task.limits(),
+            Limits {
+                time: Some(Duration::from_secs(1)),
+                memory: Some(256 << 20),
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_other_requests() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let client = post(port, "GET / HTTP/1.1\r\n\r\n".to_string());
+        assert!(accept(&listener).is_err());
+        assert!(client.join().unwrap().starts_with("HTTP/1.1 405"));
+
+        let client = post(port, "POST / HTTP/1.1\r\nContent-Length: 2\r\n\r\n{}".to_string());
+        assert!(accept(&listener).is_err());
+        assert!(client.join().unwrap().starts_with("HTTP/1.1 400"));
+
+        let client = post(port, "POST / HTTP/1.1\r\nContent-Length: 99999999999999\r\n\r\n".to_string());
+        let error = accept(&listener).unwrap_err();
+        assert!(error.to_string().contains("exceeds"), "{}", error);
+        assert!(client.join().unwrap().starts_with("HTTP/1.1 400"));
+    }
+
+    #[test]
+    fn falls_back_to_name_for_id() {
+        let task: Task = serde_json::from_str(r#"{"name": "Problem 3. Sum (easy)", "url": "https://example.com/3"}"#).unwrap();
+        assert_eq!(task.id(), "Problem_3_Sum_easy");
+        assert!(task.tests.is_empty());
+    }
+}
@@ -0,0 +1,128 @@
+/*
+ * Whitespace- and float-tolerant output comparison
+ */
+
+use std::fmt;
+
+/// First difference between an expected and an actual output.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mismatch {
+    /// 1-based index of the differing token.
+    pub token: usize,
+    /// 1-based line of the token in the expected output (or the actual one
+    /// when the expected output ended early).
+    pub line: usize,
+    pub expected: Option<String>,
+    pub actual: Option<String>,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (&self.expected, &self.actual) {
+            (Some(expected), Some(actual)) => write!(
+                f,
+                "token {} (line {}): expected `{}`, found `{}`",
+                self.token, self.line, expected, actual
+            ),
+            (Some(expected), None) => write!(
+                f,
+                "token {} (line {}): expected `{}`, output ended",
+                self.token, self.line, expected
+            ),
+            (None, Some(actual)) => write!(
+                f,
+                "token {} (line {}): extra output `{}`",
+                self.token, self.line, actual
+            ),
+            (None, None) => write!(f, "token {}: outputs differ", self.token),
+        }
+    }
+}
+
+/// Whitespace-separated tokens with their 1-based line numbers.
+pub fn tokens(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.lines()
+        .enumerate()
+        .flat_map(|(i, line)| line.split_whitespace().map(move |token| (i + 1, token)))
+}
+
+/// Whether two tokens are equal, treating them as floats within `tolerance`
+/// (absolute or relative) when the expected token is a real number.
+pub fn tokens_match(expected: &str, actual: &str, tolerance: f64) -> bool {
+    if expected == actual {
+        return true;
+    }
+    let is_real = |token: &str| token.contains(['.', 'e', 'E']);
+    if !is_real(expected) {
+        return false;
+    }
+    match (expected.parse::<f64>(), actual.parse::<f64>()) {
+        (Ok(e), Ok(a)) if e.is_finite() && a.is_finite() => {
+            let diff = (e - a).abs();
+            diff <= tolerance || diff <= tolerance * e.abs()
+        }
+        _ => false,
+    }
+}
+
+/// Compares outputs token by token, ignoring the amount and kind of whitespace.
+pub fn compare_outputs(expected: &str, actual: &str, tolerance: f64) -> Result<(), Mismatch> {
+    compare_by(expected, actual, |e, a| tokens_match(e, a, tolerance))
+}
+
+/// Like `compare_outputs` with a custom token equality `same(expected, actual)`.
+pub fn compare_by(expected: &str, actual: &str, mut same: impl FnMut(&str, &str) -> bool) -> Result<(), Mismatch> {
+    let mut expected_tokens = tokens(expected);
+    let mut actual_tokens = tokens(actual);
+
+    let mut token = 0;
+    loop {
+        token += 1;
+        match (expected_tokens.next(), actual_tokens.next()) {
+            (None, None) => return Ok(()),
+            (Some((_, e)), Some((_, a))) if same(e, a) => {}
+            (e, a) => {
+                return Err(Mismatch {
+                    token,
+                    line: e.or(a).map_or(0, |(line, _)| line),
+                    expected: e.map(|(_, t)| t.to_string()),
+                    actual: a.map(|(_, t)| t.to_string()),
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ignores_whitespace_layout() {
+        assert_eq!(compare_outputs("1 2\n3\n", "1\n2 3", 1e-6), Ok(()));
+        assert_eq!(compare_outputs("YES\n", "YES  \r\n\n", 1e-6), Ok(()));
+    }
+
+    #[test]
+    fn accepts_floats_within_tolerance() {
+        assert_eq!(compare_outputs("0.3333333", "0.33333334", 1e-6), Ok(()));
+        assert_eq!(compare_outputs("1e9", "1000000500.0", 1e-6), Ok(()));
+        assert!(compare_outputs("0.5", "0.51", 1e-6).is_err());
+        // Integers are compared exactly.
+        assert!(compare_outputs("10", "10.0", 1e-6).is_err());
+    }
+
+    #[test]
+    fn reports_first_differing_token() {
+        let mismatch = compare_outputs("1 2\n3 4\n", "1 2\n3 5\n", 1e-6).unwrap_err();
+        assert_eq!(mismatch.token, 4);
+        assert_eq!(mismatch.line, 2);
+        assert_eq!(mismatch.to_string(), "token 4 (line 2): expected `4`, found `5`");
+
+        let short = compare_outputs("1 2", "1", 1e-6).unwrap_err();
+        assert_eq!(short.to_string(), "token 2 (line 1): expected `2`, output ended");
+
+        let long = compare_outputs("1", "1\n7", 1e-6).unwrap_err();
+        assert_eq!(long.to_string(), "token 2 (line 2): extra output `7`");
+    }
+}
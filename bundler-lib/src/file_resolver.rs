@@ -2,51 +2,124 @@
  * File resolver for finding module files
  */
 
+use serde::Deserialize;
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
+/// Crate name used when a resolver is built from a single library root.
+pub const DEFAULT_CRATE: &str = "cp_lib";
+
+/// How a library crate ends up in the submission.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CrateMode {
+    /// Only the modules that are used, with their items hoisted to the top level.
+    #[default]
+    Flatten,
+    /// The whole crate wrapped in `pub mod <name> { ... }`, keeping its module tree.
+    Nested,
+}
+
+/// A library crate the bundler can inline, keyed by the name used in `use` paths.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LibCrate {
+    pub name: String,
+    /// Directory that module paths are resolved against.
+    pub root: PathBuf,
+    /// Crate root file, `lib.rs` inside `root` unless `[lib] path` says otherwise.
+    pub lib_file: PathBuf,
+    pub mode: CrateMode,
+}
+
+impl LibCrate {
+    pub fn new(name: &str, root: &Path) -> Self {
+        Self {
+            name: name.to_string(),
+            root: root.to_path_buf(),
+            lib_file: root.join("lib.rs"),
+            mode: CrateMode::default(),
+        }
+    }
+}
+
+struct CrateEntry {
+    krate: LibCrate,
+    module_cache: HashMap<String, PathBuf>,
+}
+
 pub struct FileResolver {
-    lib_root: PathBuf,
-    module_cache: std::collections::HashMap<String, PathBuf>,
+    crates: Vec<CrateEntry>,
 }
 
 impl FileResolver {
     pub fn new(lib_root: &Path) -> Self {
-        let mut resolver = Self {
-            lib_root: lib_root.to_path_buf(),
-            module_cache: std::collections::HashMap::new(),
-        };
-        
-        resolver.build_cache();
+        Self::with_crates(&[LibCrate::new(DEFAULT_CRATE, lib_root)])
+    }
+
+    pub fn with_crates(crates: &[LibCrate]) -> Self {
+        let mut resolver = Self { crates: Vec::new() };
+        for krate in crates {
+            resolver.add_crate(krate.clone());
+        }
         resolver
     }
 
-    fn build_cache(&mut self) {
-        if !self.lib_root.exists() {
-            return;
+    /// Registers a crate, replacing any earlier crate with the same name.
+    pub fn add_crate(&mut self, krate: LibCrate) {
+        self.crates.retain(|entry| entry.krate.name != krate.name);
+        let module_cache = Self::build_cache(&krate.root);
+        self.crates.push(CrateEntry {
+            krate,
+            module_cache,
+        });
+    }
+
+    fn build_cache(lib_root: &Path) -> HashMap<String, PathBuf> {
+        let mut module_cache = HashMap::new();
+        if !lib_root.exists() {
+            return module_cache;
         }
 
-        for entry in WalkDir::new(&self.lib_root)
+        for entry in WalkDir::new(lib_root)
             .follow_links(true)
             .into_iter()
             .filter_map(|e| e.ok())
         {
             let path = entry.path();
             
-            if path.is_file() && path.extension().map_or(false, |ext| ext == "rs") {
-                if let Some(relative_path) = path.strip_prefix(&self.lib_root).ok() {
+            if path.is_file() && path.extension().is_some_and(|ext| ext == "rs") {
+                if let Ok(relative_path) = path.strip_prefix(lib_root) {
                     let key = relative_path.to_string_lossy().to_string();
-                    self.module_cache.insert(key, path.to_path_buf());
+                    module_cache.insert(key, path.to_path_buf());
                 }
             }
         }
+        module_cache
+    }
+
+    pub fn get_crate(&self, name: &str) -> Option<&LibCrate> {
+        self.crates
+            .iter()
+            .map(|entry| &entry.krate)
+            .find(|krate| krate.name == name)
+    }
+
+    pub fn crates(&self) -> impl Iterator<Item = &LibCrate> {
+        self.crates.iter().map(|entry| &entry.krate)
     }
 
+    /// Resolves a module path inside the default `cp_lib` crate.
     pub fn resolve_module_file(&self, module_path: &Path) -> Option<PathBuf> {
+        self.resolve_in_crate(DEFAULT_CRATE, module_path)
+    }
+
+    pub fn resolve_in_crate(&self, crate_name: &str, module_path: &Path) -> Option<PathBuf> {
+        let entry = self.crates.iter().find(|e| e.krate.name == crate_name)?;
         let key = module_path.to_string_lossy().to_string();
         
         // Direct cache lookup
-        if let Some(cached_path) = self.module_cache.get(&key) {
+        if let Some(cached_path) = entry.module_cache.get(&key) {
             return Some(cached_path.clone());
         }
 
@@ -71,14 +144,14 @@ impl FileResolver {
         ];
 
         for variation in variations {
-            let full_path = self.lib_root.join(&variation);
+            let full_path = entry.krate.root.join(&variation);
             if full_path.exists() && full_path.is_file() {
                 return Some(full_path);
             }
 
             // Also check in cache with this variation
             let var_key = variation.to_string_lossy().to_string();
-            if let Some(cached_path) = self.module_cache.get(&var_key) {
+            if let Some(cached_path) = entry.module_cache.get(&var_key) {
                 return Some(cached_path.clone());
             }
         }
@@ -86,8 +159,33 @@ impl FileResolver {
         None
     }
 
+    /// File of a crate defining `#[macro_export] macro_rules! <name>`, which
+    /// is used as `<crate>::<name>` wherever in the crate it is defined.
+    pub fn find_exported_macro(&self, crate_name: &str, name: &str) -> Option<PathBuf> {
+        let entry = self.crates.iter().find(|e| e.krate.name == crate_name)?;
+        let mut files: Vec<&PathBuf> = entry.module_cache.values().collect();
+        files.sort();
+        files.into_iter().find(|path| {
+            let Ok(content) = std::fs::read_to_string(path) else {
+                return false;
+            };
+            let Ok(ast) = syn::parse_file(&content) else {
+                return false;
+            };
+            ast.items.iter().any(|item| {
+                matches!(item, syn::Item::Macro(mac)
+                    if mac.ident.as_ref().is_some_and(|ident| ident == name)
+                        && mac.attrs.iter().any(|attr| attr.path().is_ident("macro_export")))
+            })
+        })
+        .cloned()
+    }
+
     pub fn list_all_files(&self) -> Vec<&PathBuf> {
-        self.module_cache.values().collect()
+        self.crates
+            .iter()
+            .flat_map(|entry| entry.module_cache.values())
+            .collect()
     }
 }
 
@@ -103,7 +201,7 @@ mod tests {
         let lib_root = temp_dir.path().join("lib");
         
         // Create test structure
-        fs::create_dir_all(&lib_root.join("io")).unwrap();
+        fs::create_dir_all(lib_root.join("io")).unwrap();
         fs::write(lib_root.join("lib.rs"), "").unwrap();
         fs::write(lib_root.join("io").join("mod.rs"), "").unwrap();
         fs::write(lib_root.join("io").join("scanner.rs"), "").unwrap();
@@ -114,4 +212,29 @@ mod tests {
         assert!(resolver.resolve_module_file(Path::new("io/mod.rs")).is_some());
         assert!(resolver.resolve_module_file(Path::new("nonexistent.rs")).is_none());
     }
+
+    #[test]
+    fn resolves_modules_per_crate() {
+        let temp_dir = tempdir().unwrap();
+        let team = temp_dir.path().join("team").join("src");
+        let snippets = temp_dir.path().join("snippets").join("src");
+        fs::create_dir_all(team.join("io")).unwrap();
+        fs::create_dir_all(&snippets).unwrap();
+        fs::write(team.join("io").join("scanner.rs"), "").unwrap();
+        fs::write(snippets.join("dsu.rs"), "").unwrap();
+
+        let resolver = FileResolver::with_crates(&[
+            LibCrate::new("cp_lib", &team),
+            LibCrate::new("snippets", &snippets),
+        ]);
+
+        assert_eq!(
+            resolver.resolve_in_crate("snippets", Path::new("dsu.rs")),
+            Some(snippets.join("dsu.rs"))
+        );
+        assert!(resolver.resolve_in_crate("snippets", Path::new("io/scanner.rs")).is_none());
+        assert!(resolver.resolve_module_file(Path::new("io/scanner.rs")).is_some());
+        assert!(resolver.resolve_in_crate("unknown", Path::new("dsu.rs")).is_none());
+        assert_eq!(resolver.list_all_files().len(), 2);
+    }
 }
@@ -25,6 +25,33 @@ impl FilenameGenerator {
         format!("{}_{}.rs", formatted_name, timestamp)
     }
 
+    /// Expands `{id}`, `{problem}` and `{timestamp}` in a naming template.
+    /// Missing values expand to nothing and the leftover separators are collapsed.
+    pub fn render_template(&self, template: &str, id: Option<&str>, problem_name: Option<&str>) -> String {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        let problem = problem_name
+            .map(|name| self.format_problem_name(name))
+            .unwrap_or_default();
+
+        let rendered = template
+            .replace("{id}", id.unwrap_or_default())
+            .replace("{problem}", &problem)
+            .replace("{timestamp}", &timestamp.to_string());
+
+        let rendered = self.cleanup_regex.replace_all(&rendered, "_");
+        let rendered = rendered.trim_start_matches('_').replace("_.rs", ".rs");
+
+        if rendered.ends_with(".rs") {
+            rendered
+        } else {
+            format!("{}.rs", rendered)
+        }
+    }
+
     fn format_problem_name(&self, problem_name: &str) -> String {
         let mut filename = String::new();
         
@@ -98,4 +125,17 @@ mod tests {
         assert!(filename.ends_with(".rs"));
         assert!(filename.len() > "a._test_problem_.rs".len()); // Should have timestamp
     }
+
+    #[test]
+    fn test_render_template() {
+        let generator = FilenameGenerator::new();
+
+        assert_eq!(generator.render_template("{id}.rs", Some("1234A"), None), "1234A.rs");
+        assert_eq!(
+            generator.render_template("{id}_{problem}", Some("B"), Some("B. Two Arrays")),
+            "B_b._two_arrays.rs"
+        );
+        assert_eq!(generator.render_template("{id}_{problem}.rs", None, Some("C. Tree")), "c._tree.rs");
+        assert!(generator.render_template("cf_{timestamp}.rs", None, None).starts_with("cf_"));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,328 @@
+/*
+ * Interactive runner: solution and interactor talking through pipes
+ */
+
+use crate::runner::{self, Verdict};
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone)]
+pub struct InteractiveOptions {
+    /// Wall-clock limit for the whole interaction; both processes are killed after it.
+    pub timeout: Duration,
+    /// Maximum number of lines the solution may send.
+    pub query_limit: Option<usize>,
+}
+
+impl Default for InteractiveOptions {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(5),
+            query_limit: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Direction {
+    /// Solution to interactor, shown as `>`.
+    Query,
+    /// Interactor to solution, shown as `<`.
+    Response,
+}
+
+/// Every line exchanged, in the order the runner relayed it.
+#[derive(Debug, Clone, Default)]
+pub struct Transcript {
+    pub lines: Vec<(Direction, String)>,
+}
+
+impl fmt::Display for Transcript {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (direction, line) in &self.lines {
+            let marker = match direction {
+                Direction::Query => '>',
+                Direction::Response => '<',
+            };
+            writeln!(f, "{} {}", marker, line)?;
+        }
+        Ok(())
+    }
+}
+
+pub struct InteractiveResult {
+    pub verdict: Verdict,
+    pub transcript: Transcript,
+    pub queries: usize,
+    pub elapsed: Duration,
+}
+
+/// Copies `from` to `to` line by line, flushing after each line and logging it.
+/// Returns the number of lines relayed. Stops early when `limit` lines were
+/// exceeded (setting `over_limit`) or when `to` is closed.
+fn relay(
+    from: impl Read,
+    mut to: impl Write,
+    direction: Direction,
+    transcript: Arc<Mutex<Transcript>>,
+    limit: Option<usize>,
+    over_limit: Arc<AtomicBool>,
+) -> usize {
+    let mut reader = BufReader::new(from);
+    let mut count = 0;
+    let mut line = String::new();
+    loop {
+        line.clear();
+        match reader.read_line(&mut line) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {}
+        }
+        count += 1;
+        transcript
+            .lock()
+            .unwrap()
+            .lines
+            .push((direction, line.trim_end_matches(['\r', '\n']).to_string()));
+        if limit.is_some_and(|limit| count > limit) {
+            over_limit.store(true, Ordering::Relaxed);
+            break;
+        }
+        if to.write_all(line.as_bytes()).and_then(|_| to.flush()).is_err() {
+            break;
+        }
+    }
+    count
+}
+
+fn collect(mut stream: impl Read + Send + 'static) -> JoinHandle<String> {
+    thread::spawn(move || {
+        let mut text = String::new();
+        let _ = stream.read_to_string(&mut text);
+        text
+    })
+}
+
+/// Runs `solution` against `interactor`, which is started as
+/// `interactor <input>` and judges like a checker: exit code 0 accepts,
+/// 1 or 2 rejects with the first line of its stderr as the message.
+pub fn run_interactive(
+    solution: &Path,
+    interactor: &Path,
+    input: &Path,
+    options: &InteractiveOptions,
+) -> io::Result<InteractiveResult> {
+    let start = Instant::now();
+    let piped = |command: &mut Command| {
+        command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+    };
+    let mut interactor_process = piped(Command::new(interactor).arg(input))?;
+    let mut solution_process = match piped(&mut Command::new(solution)) {
+        Ok(process) => process,
+        Err(e) => {
+            let _ = interactor_process.kill();
+            return Err(e);
+        }
+    };
+
+    let transcript = Arc::new(Mutex::new(Transcript::default()));
+    let over_limit = Arc::new(AtomicBool::new(false));
+    let take = |child: &mut Child| {
+        (
+            child.stdin.take().expect("stdin is piped"),
+            child.stdout.take().expect("stdout is piped"),
+            child.stderr.take().expect("stderr is piped"),
+        )
+    };
+    let (solution_in, solution_out, solution_err) = take(&mut solution_process);
+    let (interactor_in, interactor_out, interactor_err) = take(&mut interactor_process);
+
+    let queries = {
+        let (transcript, over_limit) = (transcript.clone(), over_limit.clone());
+        let limit = options.query_limit;
+        thread::spawn(move || relay(solution_out, interactor_in, Direction::Query, transcript, limit, over_limit))
+    };
+    let responses = {
+        let (transcript, over_limit) = (transcript.clone(), Arc::new(AtomicBool::new(false)));
+        thread::spawn(move || relay(interactor_out, solution_in, Direction::Response, transcript, None, over_limit))
+    };
+    let solution_stderr = collect(solution_err);
+    let interactor_stderr = collect(interactor_err);
+
+    // Wait for both processes, killing them on timeout or when the query limit is hit.
+    let deadline = start + options.timeout;
+    let mut timed_out = false;
+    let (solution_status, interactor_status) = loop {
+        if over_limit.load(Ordering::Relaxed) || Instant::now() >= deadline {
+            timed_out = !over_limit.load(Ordering::Relaxed);
+            let _ = solution_process.kill();
+            let _ = interactor_process.kill();
+        }
+        match (solution_process.try_wait()?, interactor_process.try_wait()?) {
+            (Some(s), Some(i)) => break (s, i),
+            _ => thread::sleep(Duration::from_millis(1)),
+        }
+    };
+    let elapsed = start.elapsed();
+
+    let queries = queries.join().unwrap_or(0);
+    let _ = responses.join();
+    let solution_stderr = solution_stderr.join().unwrap_or_default();
+    let interactor_stderr = interactor_stderr.join().unwrap_or_default();
+    let transcript = Arc::try_unwrap(transcript)
+        .map(|m| m.into_inner().unwrap())
+        .unwrap_or_else(|shared| shared.lock().unwrap().clone());
+
+    let interactor_message = interactor_stderr.lines().next().unwrap_or("").trim().to_string();
+    let verdict = if timed_out {
+        Verdict::TimeLimitExceeded
+    } else if over_limit.load(Ordering::Relaxed) {
+        Verdict::WrongAnswer(format!(
+            "query limit exceeded (more than {})",
+            options.query_limit.unwrap_or_default()
+        ))
+    } else if matches!(interactor_status.code(), Some(1) | Some(2)) {
+        // The interactor's verdict wins; a solution that crashed because the
+        // interactor hung up on it got a wrong answer.
+        Verdict::WrongAnswer(interactor_message)
+    } else if !solution_status.success() {
+        Verdict::RuntimeError(
+            format!("{} {}", solution_status, runner::error_summary(&solution_stderr))
+                .trim_end()
+                .to_string(),
+        )
+    } else if !interactor_status.success() {
+        return Err(io::Error::other(format!(
+            "interactor failed ({}): {}",
+            interactor_status, interactor_message
+        )));
+    } else {
+        Verdict::Accepted
+    };
+
+    Ok(InteractiveResult {
+        verdict,
+        transcript,
+        queries,
+        elapsed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project_config::JudgeProfile;
+    use std::fs;
+    use std::path::PathBuf;
+    use tempfile::tempdir;
+
+    fn build(dir: &Path, name: &str, source: &str) -> PathBuf {
+        let path = dir.join(format!("{}.rs", name));
+        fs::write(&path, source).unwrap();
+        let binary = dir.join(name);
+        runner::compile(&path, &binary, &JudgeProfile::default()).unwrap();
+        binary
+    }
+
+    /// Guess-the-number interactor: reads the secret from the input file and
+    /// answers `<`, `>` or `=` to `? x` queries.
+    const INTERACTOR: &str = r#"
+        use std::io::{BufRead, Write};
+        fn main() {
+            let secret: i64 = std::fs::read_to_string(std::env::args().nth(1).unwrap())
+                .unwrap().trim().parse().unwrap();
+            let stdin = std::io::stdin();
+            let mut out = std::io::stdout();
+            writeln!(out, "100").unwrap();
+            out.flush().unwrap();
+            for line in stdin.lock().lines() {
+                let line = line.unwrap();
+                let guess: i64 = line[2..].trim().parse().unwrap();
+                let reply = if guess < secret { "<" } else if guess > secret { ">" } else { "=" };
+                if line.starts_with('!') {
+                    if guess == secret { std::process::exit(0); }
+                    eprintln!("wrong guess {}", guess);
+                    std::process::exit(1);
+                }
+                writeln!(out, "{}", reply).unwrap();
+                out.flush().unwrap();
+            }
+            eprintln!("no answer");
+            std::process::exit(1);
+        }
+    "#;
+
+    /// Binary search that reads responses line by line, like `Scanner` does.
+    const SOLUTION: &str = r#"
+        use std::io::{BufRead, Write};
+        fn main() {
+            let stdin = std::io::stdin();
+            let mut lines = stdin.lock().lines();
+            let n: i64 = lines.next().unwrap().unwrap().trim().parse().unwrap();
+            let (mut lo, mut hi) = (1, n);
+            loop {
+                let mid = (lo + hi) / 2;
+                println!("? {}", mid);
+                std::io::stdout().flush().unwrap();
+                match lines.next().unwrap().unwrap().trim() {
+                    "<" => lo = mid + 1,
+                    ">" => hi = mid - 1,
+                    _ => { println!("! {}", mid); return; }
+                }
+            }
+        }
+    "#;
+
+    #[test]
+    fn relays_and_judges_an_interaction() {
+        let dir = tempdir().unwrap();
+        let interactor = build(dir.path(), "interactor", INTERACTOR);
+        let solution = build(dir.path(), "solution", SOLUTION);
+        let input = dir.path().join("1.in");
+        fs::write(&input, "37\n").unwrap();
+
+        let result = run_interactive(&solution, &interactor, &input, &InteractiveOptions::default()).unwrap();
+        assert_eq!(result.verdict, Verdict::Accepted);
+        let transcript = result.transcript.to_string();
+        assert!(transcript.starts_with("< 100\n> ? 50\n< >\n"));
+        assert!(transcript.ends_with("> ! 37\n"));
+        assert_eq!(result.queries, result.transcript.lines.iter().filter(|(d, _)| *d == Direction::Query).count());
+
+        let limited = InteractiveOptions {
+            query_limit: Some(3),
+            ..Default::default()
+        };
+        let result = run_interactive(&solution, &interactor, &input, &limited).unwrap();
+        assert_eq!(result.verdict, Verdict::WrongAnswer("query limit exceeded (more than 3)".to_string()));
+    }
+
+    #[test]
+    fn times_out_a_silent_solution() {
+        let dir = tempdir().unwrap();
+        let interactor = build(dir.path(), "interactor", INTERACTOR);
+        let solution = build(
+            dir.path(),
+            "sleepy",
+            "fn main() { std::thread::sleep(std::time::Duration::from_secs(30)); }",
+        );
+        let input = dir.path().join("1.in");
+        fs::write(&input, "5\n").unwrap();
+
+        let options = InteractiveOptions {
+            timeout: Duration::from_millis(300),
+            query_limit: None,
+        };
+        let result = run_interactive(&solution, &interactor, &input, &options).unwrap();
+        assert_eq!(result.verdict, Verdict::TimeLimitExceeded);
+        assert!(result.elapsed < Duration::from_secs(5));
+    }
+}
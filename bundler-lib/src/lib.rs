@@ -5,68 +5,196 @@
  */
 
 pub mod ast_bundler;
+pub mod cargo_manifest;
+pub mod cfg_eval;
+pub mod checker;
+pub mod compare;
+pub mod companion;
 pub mod file_resolver;
 pub mod filename_generator;
+pub mod interactive;
+pub mod limits;
+pub mod problems;
+pub mod project_config;
+pub mod runner;
+pub mod samples;
+pub mod scaffold;
+pub mod stress;
 
 pub use ast_bundler::AstBundler;
-pub use file_resolver::FileResolver;
+pub use cargo_manifest::CargoManifest;
+pub use cfg_eval::CfgSet;
+pub use file_resolver::{CrateMode, FileResolver, LibCrate, DEFAULT_CRATE};
 pub use filename_generator::FilenameGenerator;
+pub use problems::Problem;
+pub use project_config::{JudgeProfile, MinifyOptions, ProjectConfig, ScaffoldOptions, TestOptions};
 
 use std::collections::HashSet;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use syn::{File as SynFile, Item, ItemConst};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct BundlerConfig {
     pub main_file: PathBuf,
-    pub lib_root: PathBuf,
+    /// Source root of the `cp_lib` crate. When unset, `cp_lib` comes from
+    /// discovery or else `DEFAULT_LIB_ROOT`.
+    pub lib_root: Option<PathBuf>,
+    /// Additional library crates; an entry named `cp_lib` replaces `lib_root`.
+    pub crates: Vec<LibCrate>,
+    /// Also bundle path dependencies from the nearest `Cargo.toml` that the
+    /// main file references. Entries in `crates` and `lib_root` take precedence.
+    pub discover_crates: bool,
     pub output_dir: PathBuf,
+    /// Directories holding one solution file per problem.
+    pub problem_dirs: Vec<PathBuf>,
+    /// Files inside `problem_dirs` that are not solutions.
+    pub problem_exclude: Vec<PathBuf>,
+    /// ID used when the main file declares no `_ID`.
+    pub default_id: Option<String>,
     pub create_versioned_copy: bool,
+    /// Output filename template with `{id}`, `{problem}` and `{timestamp}`.
+    pub naming: Option<String>,
+    /// cfg options enabled on the judge (see `CfgSet`).
+    pub cfg: Vec<String>,
+    pub judge: JudgeProfile,
+    pub minify: MinifyOptions,
+    pub scaffold: ScaffoldOptions,
+    pub test: TestOptions,
 }
 
 impl Default for BundlerConfig {
     fn default() -> Self {
         Self {
             main_file: PathBuf::from("src/main.rs"),
-            lib_root: PathBuf::from("cp-lib/src"),
+            lib_root: None,
+            crates: Vec::new(),
+            discover_crates: true,
             output_dir: PathBuf::from("bundled"),
+            problem_dirs: vec![PathBuf::from("src/bin"), PathBuf::from("problems")],
+            problem_exclude: vec![PathBuf::from("src/bin/ast_bundler.rs")],
+            default_id: None,
             create_versioned_copy: true,
+            naming: None,
+            cfg: Vec::new(),
+            judge: JudgeProfile::default(),
+            minify: MinifyOptions::default(),
+            scaffold: ScaffoldOptions::default(),
+            test: TestOptions::default(),
         }
     }
 }
 
+impl BundlerConfig {
+    /// Where `cp_lib` is looked for when neither `lib_root` nor discovery names it.
+    pub const DEFAULT_LIB_ROOT: &'static str = "cp-lib/src";
+
+    /// Solution files found in `problem_dirs`.
+    pub fn problems(&self) -> Vec<Problem> {
+        problems::discover_problems(&self.problem_dirs, &self.problem_exclude)
+    }
+
+    /// A copy of this config that bundles `problem` instead of `main_file`.
+    pub fn for_problem(&self, problem: &Problem) -> BundlerConfig {
+        BundlerConfig {
+            main_file: problem.path.clone(),
+            default_id: Some(problem.default_id()),
+            ..self.clone()
+        }
+    }
+
+    /// ID of the main file: its `_ID` const, else `default_id`.
+    pub fn problem_id(&self) -> Result<Option<String>, Box<dyn std::error::Error>> {
+        let content = fs::read_to_string(&self.main_file)
+            .map_err(|e| format!("{}: {}", self.main_file.display(), e))?;
+        let ast: SynFile = syn::parse_str(&content)?;
+        Ok(CodeBundler::extract_id(&ast).or_else(|| self.default_id.clone()))
+    }
+
+    /// Directory holding the sample tests of problem `id`.
+    pub fn tests_dir_for(&self, id: &str) -> PathBuf {
+        self.test.tests_dir.join(id)
+    }
+
+    /// All library crates to resolve, `cp_lib` from `lib_root` included.
+    pub fn lib_crates(&self) -> Vec<LibCrate> {
+        let mut crates = Vec::with_capacity(self.crates.len() + 1);
+        if !self.crates.iter().any(|krate| krate.name == DEFAULT_CRATE) {
+            let root = self.lib_root.as_deref().unwrap_or(Path::new(Self::DEFAULT_LIB_ROOT));
+            crates.push(LibCrate::new(DEFAULT_CRATE, root));
+        }
+        crates.extend(self.crates.iter().cloned());
+        crates
+    }
+
+    /// `lib_crates` plus the path dependencies of the main file's Cargo package
+    /// that `main_ast` refers to, with warnings for dependencies that were skipped.
+    pub fn resolve_crates(&self, main_ast: &SynFile) -> Result<(Vec<LibCrate>, Vec<String>), Box<dyn std::error::Error>> {
+        let mut crates = self.lib_crates();
+        if !self.discover_crates {
+            return Ok((crates, Vec::new()));
+        }
+        let Some(manifest_path) = CargoManifest::discover(&self.main_file) else {
+            return Ok((crates, Vec::new()));
+        };
+
+        let referenced = cargo_manifest::referenced_crates(main_ast);
+        let found = CargoManifest::load(&manifest_path)?.path_dependencies(&referenced)?;
+        for krate in found.crates {
+            let pinned = krate.name == DEFAULT_CRATE && self.lib_root.is_some();
+            if pinned || self.crates.iter().any(|c| c.name == krate.name) {
+                continue;
+            }
+            crates.retain(|c| c.name != krate.name);
+            crates.push(krate);
+        }
+        Ok((crates, found.skipped))
+    }
+}
+
 pub struct CodeBundler {
     config: BundlerConfig,
     resolver: FileResolver,
     filename_gen: FilenameGenerator,
-    #[allow(dead_code)]
     processed_files: HashSet<PathBuf>,
+    warnings: Vec<String>,
 }
 
 impl CodeBundler {
     pub fn new(config: BundlerConfig) -> Self {
         Self {
-            resolver: FileResolver::new(&config.lib_root),
+            resolver: FileResolver::with_crates(&config.lib_crates()),
             filename_gen: FilenameGenerator::new(),
             config,
             processed_files: HashSet::new(),
+            warnings: Vec::new(),
         }
     }
 
     pub fn bundle(&mut self) -> Result<String, Box<dyn std::error::Error>> {
         // Parse the main file
-        let main_content = fs::read_to_string(&self.config.main_file)?;
+        let main_content = fs::read_to_string(&self.config.main_file)
+            .map_err(|e| format!("{}: {}", self.config.main_file.display(), e))?;
         let main_ast: SynFile = syn::parse_str(&main_content)?;
+        let (crates, warnings) = self.config.resolve_crates(&main_ast)?;
+        self.resolver = FileResolver::with_crates(&crates);
+        self.warnings = warnings;
 
         // If an explicit ID is provided, it fully determines the output filename.
-        let explicit_id = self.extract_id(&main_ast);
+        let explicit_id = Self::extract_id(&main_ast)
+            .or_else(|| self.config.default_id.clone());
 
         // Extract problem name
-        let problem_name = self.extract_problem_name(&main_ast);
+        let problem_name = Self::extract_problem_name(&main_ast);
         
         // Generate filename
-        let output_filename = if let Some(ref id) = explicit_id {
+        let output_filename = if let Some(ref template) = self.config.naming {
+            self.filename_gen.render_template(
+                template,
+                explicit_id.as_deref(),
+                problem_name.as_deref(),
+            )
+        } else if let Some(ref id) = explicit_id {
             format!("solution_{}.rs", id)
         } else if let Some(ref name) = problem_name {
             self.filename_gen.generate_filename(name)
@@ -99,16 +227,26 @@ impl CodeBundler {
 
         println!("Code bundled successfully to: {}", output_path.display());
         println!("File size: {} bytes", bundled_code.len());
-        if explicit_id.is_some() {
-            println!("ID: {}", explicit_id.unwrap());
-        } else if problem_name.is_some() {
-            println!("Problem: {}", problem_name.unwrap());
+        if let Some(limit) = self.config.judge.size_limit {
+            if bundled_code.len() > limit {
+                println!(
+                    "WARNING: Bundle exceeds the judge size limit ({} > {} bytes)",
+                    bundled_code.len(),
+                    limit
+                );
+            }
+        }
+        if let Some(id) = explicit_id {
+            println!("ID: {}", id);
+        } else if let Some(name) = problem_name {
+            println!("Problem: {}", name);
         }
 
         Ok(output_filename)
     }
 
-    fn extract_problem_name(&self, ast: &SynFile) -> Option<String> {
+    /// Value of the `_PROBLEM` const, if declared.
+    pub fn extract_problem_name(ast: &SynFile) -> Option<String> {
         for item in &ast.items {
             if let Item::Const(ItemConst { ident, expr, .. }) = item {
                 if ident == "_PROBLEM" {
@@ -123,7 +261,8 @@ impl CodeBundler {
         None
     }
 
-    fn extract_id(&self, ast: &SynFile) -> Option<String> {
+    /// Value of the `_ID` (or `ID`) const, sanitized for use in filenames.
+    pub fn extract_id(ast: &SynFile) -> Option<String> {
         for item in &ast.items {
             let Item::Const(ItemConst { ident, expr, .. }) = item else {
                 continue;
@@ -155,16 +294,36 @@ impl CodeBundler {
         None
     }
 
+    /// Every source file the last `bundle` call read, main file included. When
+    /// bundling failed, this still lists the files read up to the failure.
+    pub fn bundled_files(&self) -> Vec<&PathBuf> {
+        let mut files: Vec<&PathBuf> = self.processed_files.iter().collect();
+        files.sort();
+        files
+    }
+
+    /// Problems found by the last `bundle` call that did not stop it, such as
+    /// path dependencies that were skipped.
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
     fn bundle_ast(&mut self, main_ast: &SynFile) -> Result<String, Box<dyn std::error::Error>> {
         let mut bundler = AstBundler::new(&self.resolver);
-        
-        // Process the main file AST
-        bundler.process_file_ast(main_ast, &self.config.main_file)?;
-        
-        // Generate the bundled code
-        let bundled = bundler.generate_bundled_code()?;
-        
-        Ok(bundled)
+        bundler.set_cfg(CfgSet::new(&self.config.cfg));
+        bundler.set_minify(self.config.minify.clone());
+
+        // Process the main file AST, then generate the bundled code
+        let bundled = bundler
+            .process_file_ast(main_ast, &self.config.main_file)
+            .and_then(|()| bundler.generate_bundled_code());
+
+        // Recorded on failure too, so fixing a broken library file re-runs the build script.
+        self.processed_files = std::iter::once(self.config.main_file.clone())
+            .chain(bundler.source_files().iter().cloned())
+            .collect();
+
+        bundled
     }
 }
 
@@ -188,9 +347,10 @@ mod tests {
         let config = BundlerConfig {
             main_file,
             // lib_root doesn't matter for this test.
-            lib_root: dir.path().to_path_buf(),
+            lib_root: Some(dir.path().to_path_buf()),
             output_dir: out_dir.clone(),
             create_versioned_copy: false,
+            ..Default::default()
         };
 
         let mut bundler = CodeBundler::new(config);
@@ -200,4 +360,211 @@ mod tests {
         assert!(out_dir.join("solution_ABC_123.rs").exists());
         assert!(!out_dir.join("solution.rs").exists());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn naming_template_and_cfg_flags_apply() {
+        let dir = tempdir().unwrap();
+        let main_file = dir.path().join("main.rs");
+        let out_dir = dir.path().join("out");
+
+        std::fs::write(
+            &main_file,
+            "const _ID: &str = \"1234B\";\n\
+             #[cfg(feature = \"local\")]\nfn only_local() {}\n\
+             #[cfg(test)]\nmod tests {}\n\
+             fn main() {}\n",
+        )
+        .unwrap();
+
+        let config = BundlerConfig {
+            main_file,
+            lib_root: Some(dir.path().to_path_buf()),
+            output_dir: out_dir.clone(),
+            naming: Some("cf_{id}.rs".to_string()),
+            cfg: vec!["feature = \"local\"".to_string()],
+            ..Default::default()
+        };
+
+        let mut bundler = CodeBundler::new(config);
+        let filename = bundler.bundle().unwrap();
+        assert_eq!(filename, "cf_1234B.rs");
+
+        let bundled = std::fs::read_to_string(out_dir.join(&filename)).unwrap();
+        assert!(bundled.contains("fn only_local"));
+        assert!(!bundled.contains("feature"));
+        assert!(!bundled.contains("mod tests"));
+    }
+
+    #[test]
+    fn bundles_flattened_and_nested_crates_together() {
+        let dir = tempdir().unwrap();
+        let team = dir.path().join("team").join("src");
+        let acl = dir.path().join("acl").join("src");
+        std::fs::create_dir_all(team.join("io")).unwrap();
+        std::fs::create_dir_all(acl.join("internal")).unwrap();
+
+        std::fs::write(team.join("io").join("scanner.rs"), "pub struct Scanner;\n").unwrap();
+        std::fs::write(acl.join("lib.rs"), "pub mod dsu;\nmod internal;\npub use dsu::Dsu;\n").unwrap();
+        std::fs::write(
+            acl.join("dsu.rs"),
+            "use crate::internal::helper;\npub struct Dsu;\nimpl Dsu { pub fn leader(&self) -> usize { helper() } }\n",
+        )
+        .unwrap();
+        std::fs::write(acl.join("internal").join("mod.rs"), "pub(crate) fn helper() -> usize { 0 }\n").unwrap();
+
+        let main_file = dir.path().join("main.rs");
+        std::fs::write(
+            &main_file,
+            "use team::io::scanner::Scanner;\nuse acl::Dsu;\n\
+             fn main() { let _sc = Scanner; let d = Dsu; let _ = d.leader(); }\n",
+        )
+        .unwrap();
+
+        let out_dir = dir.path().join("out");
+        let config = BundlerConfig {
+            main_file,
+            lib_root: Some(dir.path().join("missing")),
+            crates: vec![
+                LibCrate::new("team", &team),
+                LibCrate {
+                    mode: CrateMode::Nested,
+                    ..LibCrate::new("acl", &acl)
+                },
+            ],
+            output_dir: out_dir.clone(),
+            naming: Some("{id}out.rs".to_string()),
+            ..Default::default()
+        };
+
+        let filename = CodeBundler::new(config).bundle().unwrap();
+        let bundled = std::fs::read_to_string(out_dir.join(filename)).unwrap();
+
+        assert!(bundled.contains("pub struct Scanner"));
+        assert!(!bundled.contains("team"));
+        assert!(bundled.contains("pub mod acl"));
+        assert!(bundled.contains("mod internal {"));
+        assert!(bundled.contains("use crate :: acl :: internal :: helper"));
+        assert!(bundled.contains("pub (crate) fn helper"));
+        assert!(bundled.contains("use acl :: Dsu"));
+    }
+
+    #[test]
+    fn discovers_crates_from_cargo_manifest() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        std::fs::write(
+            root.join("Cargo.toml"),
+            "[package]\nname = \"contest\"\n\n[dependencies]\n\
+             lib = { path = \"team-lib\", package = \"team-lib\" }\n\
+             unused = { path = \"unused\" }\n",
+        )
+        .unwrap();
+        std::fs::create_dir_all(root.join("team-lib/src/math")).unwrap();
+        std::fs::write(root.join("team-lib/Cargo.toml"), "[package]\nname = \"team-lib\"\n").unwrap();
+        std::fs::write(root.join("team-lib/src/math/gcd.rs"), "pub fn gcd(a: u64, b: u64) -> u64 { if b == 0 { a } else { gcd(b, a % b) } }\n").unwrap();
+        std::fs::create_dir_all(root.join("unused/src")).unwrap();
+        std::fs::write(root.join("unused/Cargo.toml"), "[package]\nname = \"unused\"\n").unwrap();
+
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        let main_file = root.join("src").join("main.rs");
+        std::fs::write(&main_file, "const _ID: &str = \"G\";\nuse lib::math::gcd::gcd;\nfn main() { gcd(4, 6); }\n").unwrap();
+
+        let config = BundlerConfig {
+            main_file: main_file.clone(),
+            lib_root: Some(root.join("missing")),
+            output_dir: root.join("out"),
+            ..Default::default()
+        };
+        let main_ast: SynFile = syn::parse_str(&std::fs::read_to_string(&main_file).unwrap()).unwrap();
+        let names: Vec<String> = config
+            .resolve_crates(&main_ast)
+            .unwrap()
+            .0
+            .into_iter()
+            .map(|c| c.name)
+            .collect();
+        assert_eq!(names, vec!["cp_lib", "lib"]);
+
+        let filename = CodeBundler::new(config).bundle().unwrap();
+        let bundled = std::fs::read_to_string(root.join("out").join(filename)).unwrap();
+        assert!(bundled.contains("pub fn gcd"));
+        assert!(!bundled.contains("lib ::"));
+    }
+
+    #[test]
+    fn explicit_lib_root_takes_precedence_over_discovery() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        std::fs::write(
+            root.join("Cargo.toml"),
+            "[package]\nname = \"contest\"\n\n[dependencies]\ncp-lib = { path = \"cp-lib\" }\n",
+        )
+        .unwrap();
+        std::fs::create_dir_all(root.join("cp-lib/src")).unwrap();
+        std::fs::write(root.join("cp-lib/Cargo.toml"), "[package]\nname = \"cp-lib\"\n").unwrap();
+        std::fs::create_dir_all(root.join("pinned")).unwrap();
+
+        let main_file = root.join("main.rs");
+        std::fs::write(&main_file, "use cp_lib::math::gcd::gcd;\nfn main() { gcd(4, 6); }\n").unwrap();
+        let main_ast: SynFile = syn::parse_str(&std::fs::read_to_string(&main_file).unwrap()).unwrap();
+        let cp_lib_root = |config: &BundlerConfig| {
+            let (crates, _) = config.resolve_crates(&main_ast).unwrap();
+            crates.into_iter().find(|c| c.name == DEFAULT_CRATE).unwrap().root
+        };
+
+        let mut config = BundlerConfig {
+            main_file,
+            ..Default::default()
+        };
+        assert_eq!(cp_lib_root(&config), root.join("cp-lib/src"));
+        config.lib_root = Some(root.join("pinned"));
+        assert_eq!(cp_lib_root(&config), root.join("pinned"));
+    }
+
+    #[test]
+    fn reports_bundled_files() {
+        let dir = tempdir().unwrap();
+        let lib_root = dir.path().join("lib");
+        std::fs::create_dir_all(lib_root.join("math")).unwrap();
+        std::fs::write(lib_root.join("math").join("gcd.rs"), "pub fn gcd() {}\n").unwrap();
+        std::fs::write(lib_root.join("math").join("lcm.rs"), "pub fn lcm() {}\n").unwrap();
+
+        let main_file = dir.path().join("main.rs");
+        std::fs::write(&main_file, "use cp_lib::math::gcd::gcd;\nfn main() { gcd(); }\n").unwrap();
+
+        let mut bundler = CodeBundler::new(BundlerConfig {
+            main_file: main_file.clone(),
+            lib_root: Some(lib_root.clone()),
+            output_dir: dir.path().join("out"),
+            ..Default::default()
+        });
+        bundler.bundle().unwrap();
+
+        let gcd = lib_root.join("math").join("gcd.rs");
+        let mut expected = vec![&main_file, &gcd];
+        expected.sort();
+        assert_eq!(bundler.bundled_files(), expected);
+    }
+
+    #[test]
+    fn reports_bundled_files_when_a_library_file_is_broken() {
+        let dir = tempdir().unwrap();
+        let lib_root = dir.path().join("lib");
+        std::fs::create_dir_all(lib_root.join("math")).unwrap();
+        let gcd = lib_root.join("math").join("gcd.rs");
+        std::fs::write(&gcd, "pub fn gcd( {}\n").unwrap();
+
+        let main_file = dir.path().join("main.rs");
+        std::fs::write(&main_file, "use cp_lib::math::gcd::gcd;\nfn main() { gcd(); }\n").unwrap();
+
+        let mut bundler = CodeBundler::new(BundlerConfig {
+            main_file: main_file.clone(),
+            lib_root: Some(lib_root.clone()),
+            output_dir: dir.path().join("out"),
+            ..Default::default()
+        });
+        assert!(bundler.bundle().is_err());
+        assert!(bundler.bundled_files().contains(&&gcd));
+        assert!(bundler.bundled_files().contains(&&main_file));
+    }
+}
@@ -0,0 +1,139 @@
+/*
+ * Resource limits and usage measurement for local runs
+ */
+
+use std::io;
+use std::process::{Child, Command, ExitStatus};
+use std::time::{Duration, Instant};
+
+/// Limits a run is judged against, usually taken from the judge profile.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Limits {
+    /// CPU time limit.
+    pub time: Option<Duration>,
+    /// Memory limit in bytes.
+    pub memory: Option<u64>,
+}
+
+impl Limits {
+    /// Wall-clock time after which a run is killed: twice the time limit plus
+    /// a second, so a solution blocked on input does not hang the runner.
+    pub fn wall_clock(&self) -> Option<Duration> {
+        self.time.map(|time| time * 2 + Duration::from_secs(1))
+    }
+}
+
+/// What a finished process consumed.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Usage {
+    /// User plus system CPU time.
+    pub cpu_time: Duration,
+    /// Peak resident set size in bytes.
+    pub peak_memory: u64,
+}
+
+/// Installs rlimits on `command` as a safety net above `limits`: the CPU
+/// limit is rounded up by a second and the address space gets twice the
+/// memory limit, since virtual memory is always larger than what is resident.
+/// Exceeding the actual limits is detected from the measured `Usage`.
+#[cfg(unix)]
+pub fn apply(command: &mut Command, limits: &Limits) {
+    use std::os::unix::process::CommandExt;
+
+    let cpu_seconds = limits.time.map(|time| time.as_secs() + 1);
+    let address_space = limits.memory.map(|bytes| bytes.saturating_mul(2));
+    if cpu_seconds.is_none() && address_space.is_none() {
+        return;
+    }
+
+    let set = |resource, soft: u64, hard: u64| {
+        let limit = libc::rlimit {
+            rlim_cur: soft as libc::rlim_t,
+            rlim_max: hard as libc::rlim_t,
+        };
+        // SAFETY: setrlimit only reads the struct passed by reference.
+        if unsafe { libc::setrlimit(resource, &limit) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    };
+    // SAFETY: the closure only calls setrlimit, which is async-signal-safe.
+    unsafe {
+        command.pre_exec(move || {
+            if let Some(seconds) = cpu_seconds {
+                // SIGXCPU at the soft limit, SIGKILL a second later.
+                set(libc::RLIMIT_CPU, seconds, seconds + 1)?;
+            }
+            if let Some(bytes) = address_space {
+                set(libc::RLIMIT_AS, bytes, bytes)?;
+            }
+            Ok(())
+        });
+    }
+}
+
+#[cfg(not(unix))]
+pub fn apply(_command: &mut Command, _limits: &Limits) {}
+
+/// Waits for `child`, killing it at `deadline`. Returns its exit status, its
+/// resource usage where the platform reports one, and whether it was killed.
+#[cfg(unix)]
+pub fn wait(child: &mut Child, deadline: Option<Instant>) -> io::Result<(ExitStatus, Option<Usage>, bool)> {
+    use std::os::unix::process::ExitStatusExt;
+
+    let pid = child.id() as libc::pid_t;
+    let mut killed = false;
+    loop {
+        let mut status = 0;
+        // SAFETY: rusage is plain old data, all zeroes is a valid value.
+        let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+        let flags = if deadline.is_some() && !killed { libc::WNOHANG } else { 0 };
+        // SAFETY: pid is our own unreaped child; status and usage are valid out-pointers.
+        let reaped = unsafe { libc::wait4(pid, &mut status, flags, &mut usage) };
+
+        if reaped == -1 {
+            let error = io::Error::last_os_error();
+            if error.kind() == io::ErrorKind::Interrupted {
+                continue;
+            }
+            return Err(error);
+        }
+        if reaped == pid {
+            let time = |t: libc::timeval| Duration::new(t.tv_sec as u64, t.tv_usec as u32 * 1000);
+            // Linux reports kilobytes, macOS bytes.
+            let scale = if cfg!(target_os = "macos") { 1 } else { 1024 };
+            let usage = Usage {
+                cpu_time: time(usage.ru_utime) + time(usage.ru_stime),
+                peak_memory: usage.ru_maxrss as u64 * scale,
+            };
+            return Ok((ExitStatus::from_raw(status), Some(usage), killed));
+        }
+
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            child.kill()?;
+            killed = true;
+        } else {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+}
+
+#[cfg(not(unix))]
+pub fn wait(child: &mut Child, deadline: Option<Instant>) -> io::Result<(ExitStatus, Option<Usage>, bool)> {
+    let mut killed = false;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok((status, None, killed));
+        }
+        if !killed && deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            child.kill()?;
+            killed = true;
+        }
+        std::thread::sleep(Duration::from_millis(1));
+    }
+}
+
+/// Formats a byte count as `12.3 MB`.
+pub fn format_memory(bytes: u64) -> String {
+    format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+}
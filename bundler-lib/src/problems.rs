@@ -0,0 +1,224 @@
+/*
+ * Problem discovery and bundle summaries for contest workspaces
+ */
+
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// A solution file in a contest workspace.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Problem {
+    /// Path below its problem directory without extension, e.g. `a` or `cf1900/a`.
+    pub name: String,
+    pub path: PathBuf,
+}
+
+impl Problem {
+    /// Fallback ID for problems that do not declare `_ID`.
+    pub fn default_id(&self) -> String {
+        self.name.replace('/', "_")
+    }
+
+    pub fn matches(&self, selector: &str) -> bool {
+        self.name == selector
+            || self.path.file_stem().is_some_and(|stem| stem == selector)
+            || self.path == Path::new(selector)
+    }
+}
+
+/// Lists every `.rs` file below `dirs`, skipping paths in `exclude`.
+pub fn discover_problems(dirs: &[PathBuf], exclude: &[PathBuf]) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    for dir in dirs {
+        if !dir.is_dir() {
+            continue;
+        }
+        for entry in WalkDir::new(dir)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_map(|e| e.ok())
+        {
+            let path = entry.path();
+            if !path.is_file() || path.extension().is_none_or(|ext| ext != "rs") {
+                continue;
+            }
+            if exclude.iter().any(|excluded| excluded == path) {
+                continue;
+            }
+            let Ok(relative) = path.strip_prefix(dir) else {
+                continue;
+            };
+            let name = relative
+                .with_extension("")
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            problems.push(Problem {
+                name,
+                path: path.to_path_buf(),
+            });
+        }
+    }
+    problems
+}
+
+/// Picks the problems named by `selectors`. A selector may also be a path to a
+/// file outside the problem directories.
+pub fn select_problems(problems: &[Problem], selectors: &[String]) -> Result<Vec<Problem>, String> {
+    let mut selected = Vec::new();
+    for selector in selectors {
+        let matching: Vec<&Problem> = problems.iter().filter(|p| p.matches(selector)).collect();
+        match matching.as_slice() {
+            [] if Path::new(selector).is_file() => {
+                let path = PathBuf::from(selector);
+                let name = path
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                selected.push(Problem { name, path });
+            }
+            [] => return Err(format!("no problem matches '{}'", selector)),
+            [problem] => selected.push((*problem).clone()),
+            _ => {
+                let names: Vec<&str> = matching.iter().map(|p| p.name.as_str()).collect();
+                return Err(format!("'{}' is ambiguous: {}", selector, names.join(", ")));
+            }
+        }
+    }
+    Ok(selected)
+}
+
+/// Outcome of bundling one problem.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BundleStatus {
+    Verified,
+    Unverified,
+    CompileError,
+    Failed(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct BundleReport {
+    pub problem: String,
+    pub output: Option<String>,
+    pub size: usize,
+    pub status: BundleStatus,
+    pub over_size_limit: bool,
+}
+
+/// Renders the reports as a plain text table.
+pub fn format_summary(reports: &[BundleReport]) -> String {
+    let rows: Vec<[String; 4]> = reports
+        .iter()
+        .map(|r| {
+            let status = match &r.status {
+                BundleStatus::Verified => "OK".to_string(),
+                BundleStatus::Unverified => "not verified".to_string(),
+                BundleStatus::CompileError => "COMPILE ERROR".to_string(),
+                BundleStatus::Failed(e) => format!("FAILED: {}", e),
+            };
+            let size = if r.output.is_none() {
+                "-".to_string()
+            } else if r.over_size_limit {
+                format!("{} (over limit)", r.size)
+            } else {
+                r.size.to_string()
+            };
+            [
+                r.problem.clone(),
+                r.output.clone().unwrap_or_else(|| "-".to_string()),
+                size,
+                status,
+            ]
+        })
+        .collect();
+
+    let header = ["Problem", "Output", "Bytes", "Status"].map(String::from);
+    let mut widths = header.clone().map(|h| h.len());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    let render = |row: &[String; 4]| {
+        row.iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+
+    let mut out = render(&header);
+    out.push('\n');
+    out.push_str(&widths.map(|w| "-".repeat(w)).join("  "));
+    out.push('\n');
+    for row in &rows {
+        out.push_str(&render(row));
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn discovers_and_selects_problems() {
+        let dir = tempdir().unwrap();
+        let bin = dir.path().join("src").join("bin");
+        let contests = dir.path().join("problems");
+        fs::create_dir_all(&bin).unwrap();
+        fs::create_dir_all(contests.join("cf1900")).unwrap();
+        for file in ["a.rs", "b.rs", "ast_bundler.rs"] {
+            fs::write(bin.join(file), "fn main() {}").unwrap();
+        }
+        fs::write(contests.join("cf1900").join("c.rs"), "fn main() {}").unwrap();
+        fs::write(contests.join("notes.txt"), "").unwrap();
+
+        let problems = discover_problems(
+            &[bin.clone(), contests.clone()],
+            &[bin.join("ast_bundler.rs")],
+        );
+        let names: Vec<&str> = problems.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["a", "b", "cf1900/c"]);
+        assert_eq!(problems[2].default_id(), "cf1900_c");
+
+        let selected = select_problems(&problems, &["c".to_string(), "a".to_string()]).unwrap();
+        assert_eq!(selected[0].path, contests.join("cf1900").join("c.rs"));
+        assert_eq!(selected[1].path, bin.join("a.rs"));
+
+        assert!(select_problems(&problems, &["z".to_string()]).is_err());
+    }
+
+    #[test]
+    fn formats_summary_table() {
+        let table = format_summary(&[
+            BundleReport {
+                problem: "a".to_string(),
+                output: Some("solution_1900A.rs".to_string()),
+                size: 1234,
+                status: BundleStatus::Verified,
+                over_size_limit: false,
+            },
+            BundleReport {
+                problem: "b".to_string(),
+                output: None,
+                size: 0,
+                status: BundleStatus::Failed("parse error".to_string()),
+                over_size_limit: false,
+            },
+        ]);
+
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines[0], "Problem  Output             Bytes  Status");
+        assert_eq!(lines[2], "a        solution_1900A.rs  1234   OK");
+        assert_eq!(lines[3], "b        -                  -      FAILED: parse error");
+    }
+}
@@ -0,0 +1,440 @@
+/*
+ * Project configuration loaded from cp-rs.toml
+ */
+
+use crate::checker::CheckerSpec;
+use crate::file_resolver::{CrateMode, LibCrate};
+use crate::limits::Limits;
+use crate::BundlerConfig;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+pub const CONFIG_FILE_NAME: &str = "cp-rs.toml";
+
+/// Converts a duration in seconds given as `name`, rejecting values that are
+/// not finite and positive instead of letting `Duration` panic on them.
+pub fn seconds(name: &str, secs: f64) -> Result<Duration, String> {
+    match Duration::try_from_secs_f64(secs) {
+        Ok(duration) if !duration.is_zero() => Ok(duration),
+        _ => Err(format!("{} must be a positive number of seconds, got {}", name, secs)),
+    }
+}
+
+/// Converts a memory limit in megabytes given as `name` to bytes, rejecting
+/// zero and values whose byte count overflows a `u64`.
+pub fn megabytes(name: &str, mb: u64) -> Result<u64, String> {
+    match mb.checked_mul(1024 * 1024) {
+        Some(bytes) if bytes > 0 => Ok(bytes),
+        _ => Err(format!("{} must be a positive number of megabytes up to {}, got {}", name, u64::MAX >> 20, mb)),
+    }
+}
+
+/// Contents of a `cp-rs.toml` file. Every section is optional, missing
+/// values fall back to `BundlerConfig::default()`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProjectConfig {
+    pub bundler: BundlerSection,
+    /// Library crates keyed by the name used in `use` paths.
+    pub crates: BTreeMap<String, CrateSection>,
+    pub judge: JudgeProfile,
+    pub minify: MinifyOptions,
+    pub scaffold: ScaffoldSection,
+    pub test: TestSection,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CrateSection {
+    pub root: PathBuf,
+    #[serde(default)]
+    pub mode: CrateMode,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BundlerSection {
+    pub main_file: Option<PathBuf>,
+    pub lib_root: Option<PathBuf>,
+    pub output_dir: Option<PathBuf>,
+    pub create_versioned_copy: Option<bool>,
+    /// Directories with one solution file per problem, e.g. `src/bin` or `problems`.
+    pub problem_dirs: Option<Vec<PathBuf>>,
+    /// Files inside `problem_dirs` that are not solutions.
+    pub exclude: Option<Vec<PathBuf>>,
+    /// Pick up path dependencies from Cargo.toml (on by default).
+    pub discover_crates: Option<bool>,
+    /// Output filename template, e.g. `"{id}_{timestamp}.rs"`.
+    pub naming: Option<String>,
+    /// cfg flags considered enabled on the judge, e.g. `["feature = \"local\""]`.
+    pub cfg: Vec<String>,
+}
+
+/// What the target judge accepts.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct JudgeProfile {
+    pub edition: String,
+    pub rustc_version: Option<String>,
+    /// Maximum accepted source size in bytes.
+    pub size_limit: Option<usize>,
+    /// Time limit per test in seconds.
+    pub time_limit: Option<f64>,
+    /// Memory limit per test in megabytes.
+    pub memory_limit: Option<u64>,
+}
+
+impl Default for JudgeProfile {
+    fn default() -> Self {
+        Self {
+            edition: "2021".to_string(),
+            rustc_version: None,
+            size_limit: None,
+            time_limit: None,
+            memory_limit: None,
+        }
+    }
+}
+
+impl JudgeProfile {
+    /// Limits local test runs are judged against.
+    pub fn limits(&self) -> Limits {
+        Limits {
+            // Checked by `seconds` and `megabytes` when loaded; an invalid
+            // value means no limit.
+            time: self.time_limit.and_then(|secs| seconds("time_limit", secs).ok()),
+            memory: self.memory_limit.and_then(|mb| megabytes("memory_limit", mb).ok()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MinifyOptions {
+    /// Drop `///` and `//!` doc comments from the bundle.
+    pub strip_doc_comments: bool,
+    /// Drop the banner comments and blank lines between items.
+    pub compact: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ScaffoldSection {
+    /// Solution template used by `ast_bundler new`.
+    pub template: Option<PathBuf>,
+    /// Where the previous solution is moved before a new one is written.
+    pub archive_dir: Option<PathBuf>,
+    pub archive: Option<bool>,
+}
+
+/// How `ast_bundler new` writes fresh solution files.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScaffoldOptions {
+    /// `None` uses the built-in template.
+    pub template: Option<PathBuf>,
+    pub archive_dir: PathBuf,
+    pub archive: bool,
+}
+
+impl Default for ScaffoldOptions {
+    fn default() -> Self {
+        Self {
+            template: None,
+            archive_dir: PathBuf::from("archive"),
+            archive: true,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TestSection {
+    /// Directory with one `<ID>/` folder of `*.in`/`*.out` files per problem.
+    pub tests_dir: Option<PathBuf>,
+    /// Absolute or relative error accepted for real-valued tokens.
+    pub float_tolerance: Option<f64>,
+    /// `tokens`, `float`, `unordered`, `yesno` or a checker source file.
+    pub checker: Option<String>,
+    /// Interactor source file or binary for interactive problems.
+    pub interactor: Option<PathBuf>,
+    /// Maximum number of lines a solution may send to the interactor.
+    pub query_limit: Option<usize>,
+    /// Seconds before an interactive run is killed.
+    pub timeout: Option<f64>,
+}
+
+/// How `ast_bundler test` finds and judges sample tests.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestOptions {
+    pub tests_dir: PathBuf,
+    pub float_tolerance: f64,
+    /// `None` uses `<tests_dir>/<ID>/checker.rs` if present, else `tokens`.
+    pub checker: Option<CheckerSpec>,
+    /// `None` uses `<tests_dir>/<ID>/interactor.rs` if present.
+    pub interactor: Option<PathBuf>,
+    pub query_limit: Option<usize>,
+    pub timeout: Duration,
+}
+
+impl Default for TestOptions {
+    fn default() -> Self {
+        Self {
+            tests_dir: PathBuf::from("tests"),
+            float_tolerance: 1e-6,
+            checker: None,
+            interactor: None,
+            query_limit: None,
+            timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+impl ProjectConfig {
+    pub fn parse(content: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let config: Self = toml::from_str(content)?;
+        if let Some(secs) = config.judge.time_limit {
+            seconds("[judge] time_limit", secs)?;
+        }
+        if let Some(mb) = config.judge.memory_limit {
+            megabytes("[judge] memory_limit", mb)?;
+        }
+        if let Some(secs) = config.test.timeout {
+            seconds("[test] timeout", secs)?;
+        }
+        Ok(config)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let content = fs::read_to_string(path)?;
+        Self::parse(&content).map_err(|e| format!("{}: {}", path.display(), e).into())
+    }
+
+    /// Looks for `cp-rs.toml` in `start` and its ancestors.
+    pub fn discover(start: &Path) -> Option<PathBuf> {
+        start
+            .ancestors()
+            .map(|dir| dir.join(CONFIG_FILE_NAME))
+            .find(|candidate| candidate.is_file())
+    }
+
+    /// Loads `path` into a `BundlerConfig`, keeping paths relative when the
+    /// config file sits in or below `cwd`.
+    pub fn load_bundler_config(path: &Path, cwd: &Path) -> Result<BundlerConfig, Box<dyn std::error::Error>> {
+        let parent = path.parent().unwrap_or(Path::new(""));
+        let base_dir = parent.strip_prefix(cwd).unwrap_or(parent);
+        Ok(Self::load(path)?.to_bundler_config(base_dir))
+    }
+
+    /// Builds a `BundlerConfig`, resolving relative paths against `base_dir`
+    /// (the directory containing the config file).
+    pub fn to_bundler_config(&self, base_dir: &Path) -> BundlerConfig {
+        let defaults = BundlerConfig::default();
+        let resolve = |path: &Option<PathBuf>, fallback: PathBuf| match path {
+            Some(p) if p.is_relative() => base_dir.join(p),
+            Some(p) => p.clone(),
+            None => base_dir.join(fallback),
+        };
+
+        BundlerConfig {
+            main_file: resolve(&self.bundler.main_file, defaults.main_file),
+            lib_root: self.bundler.lib_root.as_ref().map(|root| resolve(&Some(root.clone()), PathBuf::new())),
+            crates: self
+                .crates
+                .iter()
+                .map(|(name, section)| LibCrate {
+                    mode: section.mode,
+                    ..LibCrate::new(name, &resolve(&Some(section.root.clone()), PathBuf::new()))
+                })
+                .collect(),
+            discover_crates: self.bundler.discover_crates.unwrap_or(defaults.discover_crates),
+            output_dir: resolve(&self.bundler.output_dir, defaults.output_dir),
+            problem_dirs: match &self.bundler.problem_dirs {
+                Some(dirs) => dirs.iter().map(|d| resolve(&Some(d.clone()), PathBuf::new())).collect(),
+                None => defaults.problem_dirs.into_iter().map(|d| base_dir.join(d)).collect(),
+            },
+            problem_exclude: match &self.bundler.exclude {
+                Some(files) => files.iter().map(|f| resolve(&Some(f.clone()), PathBuf::new())).collect(),
+                None => defaults.problem_exclude.into_iter().map(|f| base_dir.join(f)).collect(),
+            },
+            default_id: None,
+            create_versioned_copy: self
+                .bundler
+                .create_versioned_copy
+                .unwrap_or(defaults.create_versioned_copy),
+            naming: self.bundler.naming.clone(),
+            cfg: self.bundler.cfg.clone(),
+            judge: self.judge.clone(),
+            minify: self.minify.clone(),
+            scaffold: ScaffoldOptions {
+                template: self
+                    .scaffold
+                    .template
+                    .as_ref()
+                    .map(|t| resolve(&Some(t.clone()), PathBuf::new())),
+                archive_dir: resolve(&self.scaffold.archive_dir, defaults.scaffold.archive_dir),
+                archive: self.scaffold.archive.unwrap_or(defaults.scaffold.archive),
+            },
+            test: TestOptions {
+                tests_dir: resolve(&self.test.tests_dir, defaults.test.tests_dir),
+                float_tolerance: self.test.float_tolerance.unwrap_or(defaults.test.float_tolerance),
+                checker: self.test.checker.as_deref().map(|spec| match CheckerSpec::parse(spec) {
+                    CheckerSpec::Source(path) => CheckerSpec::Source(resolve(&Some(path), PathBuf::new())),
+                    builtin => builtin,
+                }),
+                interactor: self
+                    .test
+                    .interactor
+                    .as_ref()
+                    .map(|path| resolve(&Some(path.clone()), PathBuf::new())),
+                query_limit: self.test.query_limit,
+                timeout: self
+                    .test
+                    .timeout
+                    .and_then(|secs| seconds("timeout", secs).ok())
+                    .unwrap_or(defaults.test.timeout),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn parses_full_config() {
+        let config = ProjectConfig::parse(
+            r#"
+            [bundler]
+            main_file = "src/bin/a.rs"
+            lib_root = "lib/src"
+            output_dir = "out"
+            naming = "{id}.rs"
+            cfg = ["local", 'feature = "fast"']
+
+            [crates.snippets]
+            root = "../snippets/src"
+
+            [crates.ac_library]
+            root = "/opt/ac-library-rs/src"
+            mode = "nested"
+
+            [judge]
+            edition = "2018"
+            rustc_version = "1.75.0"
+            size_limit = 65536
+            time_limit = 2.0
+            memory_limit = 256
+
+            [minify]
+            strip_doc_comments = true
+
+            [scaffold]
+            template = "templates/multi.rs"
+
+            [test]
+            float_tolerance = 1e-9
+            checker = "checkers/any_order.rs"
+            query_limit = 30
+            timeout = 2.5
+            "#,
+        )
+        .unwrap();
+
+        let bundler = config.to_bundler_config(Path::new("contest"));
+        assert_eq!(bundler.main_file, PathBuf::from("contest/src/bin/a.rs"));
+        assert_eq!(bundler.lib_root, Some(PathBuf::from("contest/lib/src")));
+        assert_eq!(
+            bundler.crates,
+            vec![
+                LibCrate {
+                    mode: CrateMode::Nested,
+                    ..LibCrate::new("ac_library", Path::new("/opt/ac-library-rs/src"))
+                },
+                LibCrate::new("snippets", Path::new("contest/../snippets/src")),
+            ]
+        );
+        assert_eq!(bundler.output_dir, PathBuf::from("contest/out"));
+        assert_eq!(bundler.naming.as_deref(), Some("{id}.rs"));
+        assert_eq!(bundler.cfg, vec!["local", "feature = \"fast\""]);
+        assert_eq!(bundler.judge.edition, "2018");
+        assert_eq!(bundler.judge.size_limit, Some(65536));
+        assert_eq!(
+            bundler.judge.limits(),
+            Limits {
+                time: Some(Duration::from_secs(2)),
+                memory: Some(256 << 20),
+            }
+        );
+        assert!(bundler.minify.strip_doc_comments);
+        assert!(!bundler.minify.compact);
+        assert_eq!(bundler.scaffold.template, Some(PathBuf::from("contest/templates/multi.rs")));
+        assert_eq!(bundler.scaffold.archive_dir, PathBuf::from("contest/archive"));
+        assert_eq!(bundler.test.tests_dir, PathBuf::from("contest/tests"));
+        assert_eq!(bundler.test.float_tolerance, 1e-9);
+        assert_eq!(
+            bundler.test.checker,
+            Some(CheckerSpec::Source(PathBuf::from("contest/checkers/any_order.rs")))
+        );
+        assert_eq!(bundler.test.query_limit, Some(30));
+        assert_eq!(bundler.test.timeout, Duration::from_millis(2500));
+    }
+
+    #[test]
+    fn empty_config_matches_defaults() {
+        let config = ProjectConfig::parse("").unwrap();
+        let bundler = config.to_bundler_config(Path::new(""));
+        let defaults = BundlerConfig::default();
+
+        assert_eq!(bundler.main_file, defaults.main_file);
+        assert_eq!(bundler.lib_root, defaults.lib_root);
+        assert_eq!(bundler.output_dir, defaults.output_dir);
+        assert_eq!(bundler.judge, JudgeProfile::default());
+    }
+
+    #[test]
+    fn rejects_unknown_keys() {
+        assert!(ProjectConfig::parse("[bundler]\nmian_file = \"x.rs\"\n").is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_durations() {
+        for value in ["-1", "0", "nan", "inf"] {
+            let error = ProjectConfig::parse(&format!("[judge]\ntime_limit = {}\n", value)).unwrap_err();
+            assert!(error.to_string().contains("time_limit must be a positive number"), "{}", error);
+        }
+        assert!(ProjectConfig::parse("[test]\ntimeout = -2.5\n").is_err());
+        assert_eq!(seconds("x", 0.5), Ok(Duration::from_millis(500)));
+    }
+
+    #[test]
+    fn rejects_invalid_memory_limits() {
+        for value in ["0", "17592186044416"] {
+            let error = ProjectConfig::parse(&format!("[judge]\nmemory_limit = {}\n", value)).unwrap_err();
+            assert!(error.to_string().contains("memory_limit must be a positive number"), "{}", error);
+        }
+        assert_eq!(megabytes("x", 256), Ok(256 << 20));
+        let judge = JudgeProfile {
+            memory_limit: Some(u64::MAX),
+            ..JudgeProfile::default()
+        };
+        assert_eq!(judge.limits().memory, None);
+    }
+
+    #[test]
+    fn discovers_config_in_ancestors() {
+        let dir = tempdir().unwrap();
+        let nested = dir.path().join("contest").join("a");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(dir.path().join(CONFIG_FILE_NAME), "").unwrap();
+
+        assert_eq!(
+            ProjectConfig::discover(&nested),
+            Some(dir.path().join(CONFIG_FILE_NAME))
+        );
+    }
+}
@@ -0,0 +1,439 @@
+/*
+ * Local test runner: compile a bundle and check it against sample files
+ */
+
+use crate::checker::Checker;
+use crate::limits::{self, Limits, Usage};
+use crate::project_config::JudgeProfile;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Stdio};
+use std::time::{Duration, Instant};
+
+/// One `<name>.in` file with its optional `<name>.out`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestCase {
+    pub name: String,
+    pub input: PathBuf,
+    pub expected: Option<PathBuf>,
+}
+
+/// Lists the `*.in` files in `dir`, sorted by name with numbers compared by value.
+pub fn discover_tests(dir: &Path) -> Vec<TestCase> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut cases: Vec<TestCase> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "in"))
+        .map(|input| {
+            let expected = input.with_extension("out");
+            TestCase {
+                name: input.file_stem().unwrap_or_default().to_string_lossy().to_string(),
+                expected: expected.is_file().then_some(expected),
+                input,
+            }
+        })
+        .collect();
+    cases.sort_by_key(|case| natural_key(&case.name));
+    cases
+}
+
+/// Sort key that orders `sample2` before `sample10`.
+fn natural_key(name: &str) -> Vec<(String, u64)> {
+    let mut key = Vec::new();
+    let mut rest = name;
+    while !rest.is_empty() {
+        let text_len = rest.find(|c: char| c.is_ascii_digit()).unwrap_or(rest.len());
+        let (text, tail) = rest.split_at(text_len);
+        let digits_len = tail.find(|c: char| !c.is_ascii_digit()).unwrap_or(tail.len());
+        let (digits, tail) = tail.split_at(digits_len);
+        key.push((text.to_string(), digits.parse().unwrap_or(0)));
+        rest = tail;
+    }
+    key
+}
+
+/// Compiles `source` the way the judge would (optimized, judge edition).
+/// On failure the compiler output is returned as the error.
+pub fn compile(source: &Path, binary: &Path, judge: &JudgeProfile) -> Result<(), Box<dyn std::error::Error>> {
+    let output = Command::new("rustc")
+        .arg(source)
+        .arg("-o")
+        .arg(binary)
+        .args(["-O", "--edition", &judge.edition, "--allow", "warnings"])
+        .output()
+        .map_err(|e| format!("rustc: {}", e))?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).into_owned().into())
+    }
+}
+
+pub struct RunOutput {
+    pub stdout: String,
+    pub stderr: String,
+    pub status: ExitStatus,
+    pub elapsed: Duration,
+    /// CPU time and peak memory, where the platform reports them.
+    pub usage: Option<Usage>,
+    /// Killed after running past the wall-clock limit.
+    pub killed: bool,
+}
+
+impl RunOutput {
+    /// Stopped for running too long, by the wall-clock kill or the CPU rlimit.
+    pub fn timed_out(&self) -> bool {
+        self.killed || cpu_limit_signal(&self.status)
+    }
+}
+
+/// Runs `binary` with `input` on stdin and collects its output.
+pub fn run(binary: &Path, input: &[u8]) -> io::Result<RunOutput> {
+    run_limited(binary, &[], input, &Limits::default())
+}
+
+/// Like `run`, passing `args` on the command line.
+pub fn run_with_args(binary: &Path, args: &[String], input: &[u8]) -> io::Result<RunOutput> {
+    run_limited(binary, args, input, &Limits::default())
+}
+
+/// Like `run_with_args`, under `limits` (see `limits::apply`).
+pub fn run_limited(binary: &Path, args: &[String], input: &[u8], limits: &Limits) -> io::Result<RunOutput> {
+    let mut command = Command::new(binary);
+    command
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    limits::apply(&mut command, limits);
+
+    let start = Instant::now();
+    let mut child = command.spawn()?;
+
+    // Feed stdin from another thread so a solution that writes before it has
+    // read everything cannot deadlock on a full pipe.
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let input = input.to_vec();
+    let writer = std::thread::spawn(move || {
+        // The solution may exit without reading all input; that is not an error here.
+        let _ = stdin.write_all(&input);
+    });
+
+    let collect = |mut stream: Box<dyn Read + Send>| {
+        std::thread::spawn(move || {
+            let mut bytes = Vec::new();
+            let _ = stream.read_to_end(&mut bytes);
+            String::from_utf8_lossy(&bytes).into_owned()
+        })
+    };
+    let stdout = collect(Box::new(child.stdout.take().expect("stdout is piped")));
+    let stderr = collect(Box::new(child.stderr.take().expect("stderr is piped")));
+
+    let deadline = limits.wall_clock().map(|limit| start + limit);
+    let (status, usage, killed) = limits::wait(&mut child, deadline)?;
+    let elapsed = start.elapsed();
+    let _ = writer.join();
+
+    Ok(RunOutput {
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+        status,
+        elapsed,
+        usage,
+        killed,
+    })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Verdict {
+    Accepted,
+    WrongAnswer(String),
+    RuntimeError(String),
+    TimeLimitExceeded,
+    MemoryLimitExceeded,
+    /// No `.out` file; the output is shown instead of judged.
+    NoExpected(String),
+}
+
+impl Verdict {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Verdict::Accepted => "AC",
+            Verdict::WrongAnswer(_) => "WA",
+            Verdict::RuntimeError(_) => "RE",
+            Verdict::TimeLimitExceeded => "TL",
+            Verdict::MemoryLimitExceeded => "ML",
+            Verdict::NoExpected(_) => "??",
+        }
+    }
+
+    pub fn is_failure(&self) -> bool {
+        matches!(
+            self,
+            Verdict::WrongAnswer(_)
+                | Verdict::RuntimeError(_)
+                | Verdict::TimeLimitExceeded
+                | Verdict::MemoryLimitExceeded
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CaseResult {
+    pub name: String,
+    pub verdict: Verdict,
+    pub elapsed: Duration,
+    pub usage: Option<Usage>,
+}
+
+/// Runs `binary` on one test case under `limits` and judges its output with `checker`.
+pub fn run_case(binary: &Path, case: &TestCase, checker: &Checker, limits: &Limits) -> io::Result<CaseResult> {
+    let input = fs::read_to_string(&case.input)?;
+    let output = run_limited(binary, &[], input.as_bytes(), limits)?;
+
+    let over_time = limits
+        .time
+        .zip(output.usage)
+        .is_some_and(|(limit, usage)| usage.cpu_time > limit);
+    let over_memory = limits
+        .memory
+        .zip(output.usage)
+        .is_some_and(|(limit, usage)| usage.peak_memory > limit);
+    // Running into the address space rlimit makes the allocator abort.
+    let out_of_memory = limits.memory.is_some() && output.stderr.contains("memory allocation of");
+
+    let verdict = if output.timed_out() || over_time {
+        Verdict::TimeLimitExceeded
+    } else if over_memory || out_of_memory {
+        Verdict::MemoryLimitExceeded
+    } else if !output.status.success() {
+        Verdict::RuntimeError(format!("{} {}", output.status, error_summary(&output.stderr)).trim_end().to_string())
+    } else if let Some(expected_path) = &case.expected {
+        let expected = fs::read_to_string(expected_path)?;
+        match checker.check(&input, &expected, &output.stdout)? {
+            Ok(()) => Verdict::Accepted,
+            Err(message) => Verdict::WrongAnswer(message),
+        }
+    } else {
+        Verdict::NoExpected(output.stdout)
+    };
+
+    Ok(CaseResult {
+        name: case.name.clone(),
+        verdict,
+        elapsed: output.elapsed,
+        usage: output.usage,
+    })
+}
+
+/// Whether the process was stopped by the CPU time rlimit.
+#[cfg(unix)]
+fn cpu_limit_signal(status: &ExitStatus) -> bool {
+    use std::os::unix::process::ExitStatusExt;
+    status.signal() == Some(libc::SIGXCPU)
+}
+
+#[cfg(not(unix))]
+fn cpu_limit_signal(_status: &ExitStatus) -> bool {
+    false
+}
+
+/// The panic message from `stderr`, or its last meaningful line.
+pub fn error_summary(stderr: &str) -> &str {
+    let mut lines = stderr.lines().map(str::trim).filter(|l| !l.is_empty());
+    if lines.by_ref().any(|l| l.contains("panicked at")) {
+        if let Some(message) = lines.next() {
+            return message;
+        }
+    }
+    stderr
+        .lines()
+        .map(str::trim)
+        .rfind(|l| !l.is_empty() && !l.starts_with("note:"))
+        .unwrap_or("")
+}
+
+/// One line per case: name, verdict, time and details, colored with ANSI codes
+/// when `color` is set.
+pub fn format_case(result: &CaseResult, name_width: usize, color: bool) -> String {
+    let code = result.verdict.code();
+    let painted = if color {
+        let ansi = match result.verdict {
+            Verdict::Accepted => "32",
+            Verdict::WrongAnswer(_) => "31",
+            Verdict::RuntimeError(_) => "35",
+            Verdict::TimeLimitExceeded | Verdict::MemoryLimitExceeded => "34",
+            Verdict::NoExpected(_) => "33",
+        };
+        format!("\x1b[1;{}m{}\x1b[0m", ansi, code)
+    } else {
+        code.to_string()
+    };
+
+    let mut line = format!(
+        "{:<width$}  {}  {:>5} ms",
+        result.name,
+        painted,
+        result.elapsed.as_millis(),
+        width = name_width
+    );
+    if let Some(usage) = result.usage {
+        line.push_str(&format!(
+            "  cpu {:>5} ms  {:>8}",
+            usage.cpu_time.as_millis(),
+            limits::format_memory(usage.peak_memory)
+        ));
+    }
+    match &result.verdict {
+        Verdict::WrongAnswer(detail) | Verdict::RuntimeError(detail) => {
+            line.push_str("  ");
+            line.push_str(detail);
+        }
+        Verdict::NoExpected(output) => {
+            line.push_str("  no expected output, got:\n");
+            line.push_str(output.trim_end());
+        }
+        Verdict::Accepted | Verdict::TimeLimitExceeded | Verdict::MemoryLimitExceeded => {}
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn discovers_cases_in_natural_order() {
+        let dir = tempdir().unwrap();
+        for file in ["sample10.in", "sample10.out", "sample2.in", "sample2.out", "extra.in", "notes.txt"] {
+            fs::write(dir.path().join(file), "").unwrap();
+        }
+
+        let cases = discover_tests(dir.path());
+        let names: Vec<&str> = cases.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["extra", "sample2", "sample10"]);
+        assert_eq!(cases[0].expected, None);
+        assert_eq!(cases[1].expected, Some(dir.path().join("sample2.out")));
+    }
+
+    #[test]
+    fn formats_verdicts() {
+        let result = CaseResult {
+            name: "sample1".to_string(),
+            verdict: Verdict::WrongAnswer("token 1 (line 1): expected `1`, found `2`".to_string()),
+            elapsed: Duration::from_millis(12),
+            usage: None,
+        };
+        assert_eq!(
+            format_case(&result, 8, false),
+            "sample1   WA     12 ms  token 1 (line 1): expected `1`, found `2`"
+        );
+        assert!(format_case(&result, 8, true).contains("\x1b[1;31mWA\x1b[0m"));
+
+        let measured = CaseResult {
+            verdict: Verdict::Accepted,
+            usage: Some(Usage {
+                cpu_time: Duration::from_millis(9),
+                peak_memory: 3 * 1024 * 1024 + 300 * 1024,
+            }),
+            ..result
+        };
+        assert_eq!(format_case(&measured, 8, false), "sample1   AC     12 ms  cpu     9 ms    3.3 MB");
+    }
+
+    #[test]
+    fn judges_a_compiled_solution() {
+        let dir = tempdir().unwrap();
+        let source = dir.path().join("sum.rs");
+        fs::write(
+            &source,
+            "use std::io::Read;\n\
+             fn main() {\n\
+                 let mut s = String::new();\n\
+                 std::io::stdin().read_to_string(&mut s).unwrap();\n\
+                 let v: Vec<i64> = s.split_whitespace().map(|t| t.parse().unwrap()).collect();\n\
+                 if v.is_empty() { panic!(\"empty input\"); }\n\
+                 println!(\"{}\", v.iter().sum::<i64>());\n\
+             }\n",
+        )
+        .unwrap();
+        let binary = dir.path().join("sum");
+        compile(&source, &binary, &JudgeProfile::default()).unwrap();
+
+        let tests = dir.path().join("tests");
+        fs::create_dir_all(&tests).unwrap();
+        fs::write(tests.join("1.in"), "1 2 3\n").unwrap();
+        fs::write(tests.join("1.out"), "6\n").unwrap();
+        fs::write(tests.join("2.in"), "1 1\n").unwrap();
+        fs::write(tests.join("2.out"), "3\n").unwrap();
+        fs::write(tests.join("3.in"), "").unwrap();
+
+        let results: Vec<Verdict> = discover_tests(&tests)
+            .iter()
+            .map(|case| run_case(&binary, case, &Checker::default(), &Limits::default()).unwrap().verdict)
+            .collect();
+
+        assert_eq!(results[0], Verdict::Accepted);
+        assert_eq!(
+            results[1],
+            Verdict::WrongAnswer("token 1 (line 1): expected `3`, found `2`".to_string())
+        );
+        assert!(matches!(&results[2], Verdict::RuntimeError(e) if e.ends_with("empty input")));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn flags_time_and_memory_limits() {
+        let dir = tempdir().unwrap();
+        let source = dir.path().join("greedy.rs");
+        // Input `t` spins forever, `m` touches 200 MB, anything else exits at once.
+        fs::write(
+            &source,
+            "fn main() {\n\
+                 let mut s = String::new();\n\
+                 std::io::stdin().read_line(&mut s).unwrap();\n\
+                 match s.trim() {\n\
+                     \"t\" => { let mut x = 0u64; loop { x = std::hint::black_box(x + 1); } }\n\
+                     \"m\" => { let v = vec![1u8; 200 << 20]; println!(\"{}\", v.iter().map(|&b| b as u64).sum::<u64>()); }\n\
+                     _ => println!(\"ok\"),\n\
+                 }\n\
+             }\n",
+        )
+        .unwrap();
+        let binary = dir.path().join("greedy");
+        compile(&source, &binary, &JudgeProfile::default()).unwrap();
+
+        let limits = Limits {
+            time: Some(Duration::from_millis(200)),
+            memory: Some(64 << 20),
+        };
+        let case = |name: &str, input: &str| {
+            let path = dir.path().join(format!("{}.in", name));
+            fs::write(&path, input).unwrap();
+            let case = TestCase {
+                name: name.to_string(),
+                input: path,
+                expected: None,
+            };
+            run_case(&binary, &case, &Checker::default(), &limits).unwrap()
+        };
+
+        let quick = case("quick", "x\n");
+        assert!(matches!(quick.verdict, Verdict::NoExpected(_)));
+        let usage = quick.usage.unwrap();
+        assert!(usage.peak_memory > 0 && usage.peak_memory < 64 << 20);
+
+        let spin = case("spin", "t\n");
+        assert_eq!(spin.verdict, Verdict::TimeLimitExceeded);
+        assert!(spin.elapsed < Duration::from_secs(5));
+
+        assert_eq!(case("memory", "m\n").verdict, Verdict::MemoryLimitExceeded);
+    }
+}
@@ -0,0 +1,264 @@
+/*
+ * Sample tests from saved problem statement pages
+ */
+
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Sample {
+    pub input: String,
+    pub output: String,
+}
+
+/// What could be read off a problem statement page.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProblemPage {
+    /// `1900A` on Codeforces, the task ID (`abc300_a`) on AtCoder.
+    pub id: Option<String>,
+    /// Title as shown on the page, e.g. `A. Watermelon`.
+    pub name: Option<String>,
+    pub samples: Vec<Sample>,
+}
+
+/// Links to a Codeforces problem: contest ID and index in groups 1 and 2 or 3 and 4.
+const CODEFORCES_LINK: &str = r"/(?:contest|gym)/(\d+)/problem/(\w+)|/problemset/problem/(\d+)/(\w+)";
+/// Links to an AtCoder task, with the task ID in group 1.
+const ATCODER_TASK: &str = r"/contests/\w+/tasks/(\w+)";
+
+fn codeforces_id(caps: &regex::Captures) -> (String, String) {
+    let contest = caps.get(1).or(caps.get(3)).unwrap().as_str();
+    let problem = caps.get(2).or(caps.get(4)).unwrap().as_str();
+    (contest.to_string(), problem.to_uppercase())
+}
+
+/// Problem ID for a Codeforces or AtCoder problem URL, in the form the page
+/// parsers use.
+pub fn id_from_url(url: &str) -> Option<String> {
+    if let Some(caps) = Regex::new(ATCODER_TASK).unwrap().captures(url) {
+        return Some(caps[1].to_string());
+    }
+    let caps = Regex::new(CODEFORCES_LINK).unwrap().captures(url)?;
+    let (contest, problem) = codeforces_id(&caps);
+    Some(format!("{}{}", contest, problem))
+}
+
+/// Parses a saved Codeforces or AtCoder problem page, picking the parser from
+/// the markup.
+pub fn parse_page(html: &str) -> Result<ProblemPage, String> {
+    let page = if html.contains("class=\"sample-test\"") {
+        parse_codeforces(html)
+    } else if html.contains("atcoder.jp") || html.contains("Sample Input") || html.contains("入力例") {
+        parse_atcoder(html)
+    } else {
+        return Err("not a Codeforces or AtCoder problem page".to_string());
+    };
+    if page.samples.is_empty() {
+        return Err("no sample tests found on the page".to_string());
+    }
+    Ok(page)
+}
+
+pub fn parse_codeforces(html: &str) -> ProblemPage {
+    let title = Regex::new(r#"(?s)class="header"\s*>\s*<div class="title"\s*>(.*?)</div>"#).unwrap();
+    let name = title.captures(html).map(|caps| text(&caps[1])).filter(|name| !name.is_empty());
+
+    // The page links to itself from the contest navigation and, when saved
+    // from a browser, records its URL in a comment; prefer the link whose
+    // index matches the title.
+    let index = name.as_deref().and_then(|name| name.split('.').next()).map(str::trim);
+    let links = Regex::new(CODEFORCES_LINK).unwrap();
+    let ids: Vec<(String, String)> = links.captures_iter(html).map(|caps| codeforces_id(&caps)).collect();
+    let id = ids
+        .iter()
+        .find(|(_, problem)| Some(problem.as_str()) == index)
+        .or(ids.first())
+        .map(|(contest, problem)| format!("{}{}", contest, problem));
+
+    let blocks = |class: &str| -> Vec<String> {
+        let pattern = format!(r#"(?s)<div class="{}"\s*>.*?<pre[^>]*>(.*?)</pre>"#, class);
+        Regex::new(&pattern)
+            .unwrap()
+            .captures_iter(html)
+            .map(|caps| sample_text(&caps[1]))
+            .collect()
+    };
+    let samples = blocks("input")
+        .into_iter()
+        .zip(blocks("output"))
+        .map(|(input, output)| Sample { input, output })
+        .collect();
+
+    ProblemPage { id, name, samples }
+}
+
+pub fn parse_atcoder(html: &str) -> ProblemPage {
+    let heading = Regex::new(r#"(?s)<span class="h2"\s*>(.*?)(?:<a |</span>)"#).unwrap();
+    let title = Regex::new(r"(?s)<title>(.*?)</title>").unwrap();
+    let name = heading
+        .captures(html)
+        .or_else(|| title.captures(html))
+        .map(|caps| text(&caps[1]))
+        .filter(|name| !name.is_empty());
+
+    let task = Regex::new(ATCODER_TASK).unwrap();
+    let id = task.captures(html).map(|caps| caps[1].to_string());
+
+    // Statements come in Japanese and English; both list the same samples.
+    let english = atcoder_samples(html, "Sample Input", "Sample Output");
+    let samples = if english.is_empty() {
+        atcoder_samples(html, "入力例", "出力例")
+    } else {
+        english
+    };
+
+    ProblemPage { id, name, samples }
+}
+
+fn atcoder_samples(html: &str, input: &str, output: &str) -> Vec<Sample> {
+    let blocks = |label: &str| -> BTreeMap<u32, String> {
+        let pattern = format!(r"(?s)<h3>\s*{}\s*(\d+)\s*</h3>.*?<pre[^>]*>(.*?)</pre>", regex::escape(label));
+        let mut found = BTreeMap::new();
+        for caps in Regex::new(&pattern).unwrap().captures_iter(html) {
+            found.entry(caps[1].parse().unwrap_or(0)).or_insert_with(|| sample_text(&caps[2]));
+        }
+        found
+    };
+    let mut outputs = blocks(output);
+    blocks(input)
+        .into_iter()
+        .filter_map(|(n, input)| Some(Sample { input, output: outputs.remove(&n)? }))
+        .collect()
+}
+
+/// Text of an HTML fragment on one line, tags removed and entities decoded.
+fn text(html: &str) -> String {
+    let tags = Regex::new(r"<[^>]*>").unwrap();
+    let spaces = Regex::new(r"\s+").unwrap();
+    let plain = decode_entities(&tags.replace_all(html, ""));
+    spaces.replace_all(plain.trim(), " ").to_string()
+}
+
+/// Contents of a `<pre>` sample block. Codeforces wraps each line in a
+/// `<div>` or separates lines with `<br>`; trailing spaces and blank lines
+/// around the sample are dropped and the text ends with a newline.
+fn sample_text(html: &str) -> String {
+    let breaks = Regex::new(r"(?i)<br\s*/?>|</div>").unwrap();
+    let tags = Regex::new(r"<[^>]*>").unwrap();
+    let raw = breaks.replace_all(html, "\n");
+    let raw = decode_entities(&tags.replace_all(&raw, ""));
+
+    let lines: Vec<&str> = raw.lines().map(str::trim_end).collect();
+    let first = lines.iter().position(|line| !line.is_empty());
+    let last = lines.iter().rposition(|line| !line.is_empty());
+    match (first, last) {
+        (Some(first), Some(last)) => lines[first..=last].join("\n") + "\n",
+        _ => String::new(),
+    }
+}
+
+fn decode_entities(text: &str) -> String {
+    let entity = Regex::new(r"&(#[0-9]+|#[xX][0-9a-fA-F]+|[a-zA-Z]+);").unwrap();
+    entity
+        .replace_all(text, |caps: &regex::Captures| {
+            let name = &caps[1];
+            let code = if let Some(hex) = name.strip_prefix("#x").or_else(|| name.strip_prefix("#X")) {
+                u32::from_str_radix(hex, 16).ok()
+            } else if let Some(decimal) = name.strip_prefix('#') {
+                decimal.parse().ok()
+            } else {
+                match name {
+                    "lt" => Some('<' as u32),
+                    "gt" => Some('>' as u32),
+                    "amp" => Some('&' as u32),
+                    "quot" => Some('"' as u32),
+                    "apos" => Some('\'' as u32),
+                    "nbsp" => Some(' ' as u32),
+                    _ => None,
+                }
+            };
+            code.and_then(char::from_u32)
+                .map_or_else(|| caps[0].to_string(), |c| c.to_string())
+        })
+        .to_string()
+}
+
+/// Writes `sample<k>.in` and `sample<k>.out` into `dir`, numbered from 1,
+/// and returns the input files written.
+pub fn write_samples(dir: &Path, samples: &[Sample]) -> io::Result<Vec<PathBuf>> {
+    fs::create_dir_all(dir)?;
+    let mut written = Vec::with_capacity(samples.len());
+    for (k, sample) in samples.iter().enumerate() {
+        let input = dir.join(format!("sample{}.in", k + 1));
+        fs::write(&input, &sample.input)?;
+        fs::write(dir.join(format!("sample{}.out", k + 1)), &sample.output)?;
+        written.push(input);
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    const CODEFORCES: &str = include_str!("../tests/fixtures/codeforces_1900A.html");
+    const ATCODER: &str = include_str!("../tests/fixtures/atcoder_abc300_a.html");
+
+    #[test]
+    fn parses_codeforces_page() {
+        let page = parse_page(CODEFORCES).unwrap();
+        assert_eq!(page.id.as_deref(), Some("1900A"));
+        assert_eq!(page.name.as_deref(), Some("A. Cover in Water"));
+        assert_eq!(
+            page.samples,
+            vec![Sample {
+                input: "5\n3\n...\n7\n##....#\n7\n..#.#..\n4\n####\n10\n#...#..#.#\n".to_string(),
+                output: "2\n2\n5\n0\n2\n".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn parses_atcoder_page() {
+        let page = parse_page(ATCODER).unwrap();
+        assert_eq!(page.id.as_deref(), Some("abc300_a"));
+        assert_eq!(page.name.as_deref(), Some("A - N-choice question"));
+        assert_eq!(page.samples.len(), 3);
+        assert_eq!(page.samples[0].input, "3 125 175\n200 300 400\n");
+        assert_eq!(page.samples[0].output, "2\n");
+        assert_eq!(page.samples[1].input, "1 1 1\n2\n");
+    }
+
+    #[test]
+    fn ids_from_urls() {
+        assert_eq!(id_from_url("https://codeforces.com/contest/1900/problem/A").as_deref(), Some("1900A"));
+        assert_eq!(id_from_url("https://codeforces.com/problemset/problem/4/a").as_deref(), Some("4A"));
+        assert_eq!(id_from_url("https://codeforces.com/gym/104114/problem/B1").as_deref(), Some("104114B1"));
+        assert_eq!(
+            id_from_url("https://atcoder.jp/contests/abc300/tasks/abc300_a").as_deref(),
+            Some("abc300_a")
+        );
+        assert_eq!(id_from_url("https://example.com/problem/1"), None);
+    }
+
+    #[test]
+    fn decodes_markup_inside_samples() {
+        assert_eq!(sample_text("\n1 &lt; 2 &amp;&amp; 3&#62;2<br/>x&nbsp; \n\n"), "1 < 2 && 3>2\nx\n");
+        assert!(parse_page("<html><body>Hello</body></html>").is_err());
+    }
+
+    #[test]
+    fn writes_numbered_sample_files() {
+        let dir = tempdir().unwrap();
+        let samples = parse_page(ATCODER).unwrap().samples;
+        let written = write_samples(&dir.path().join("abc300_a"), &samples).unwrap();
+
+        assert_eq!(written.len(), 3);
+        assert_eq!(fs::read_to_string(dir.path().join("abc300_a/sample2.out")).unwrap(), "1\n");
+    }
+}
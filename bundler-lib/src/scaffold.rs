@@ -0,0 +1,204 @@
+/*
+ * Problem scaffolding: fresh solution files from a template
+ */
+
+use crate::project_config::ScaffoldOptions;
+use crate::CodeBundler;
+use regex::Regex;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Template used when `[scaffold] template` is not set. `{{ID}}` and
+/// `{{PROBLEM}}` are replaced, and the `_ID`/`_PROBLEM` consts are filled in
+/// even if a custom template lacks the placeholders.
+pub const DEFAULT_TEMPLATE: &str = r#"use cp_lib::io::scanner::Scanner;
+use cp_lib::io::writer::Writer;
+
+const _ID: &str = "{{ID}}";
+const _PROBLEM: &str = "{{PROBLEM}}";
+
+fn main() {
+    let mut sc = Scanner::new();
+    let mut out = Writer::new();
+
+    let t: usize = sc.next();
+    for _ in 0..t {
+        let n: usize = sc.next();
+        let a: Vec<i64> = sc.dump(n);
+
+        out.println(a.iter().sum::<i64>());
+    }
+}
+"#;
+
+/// Renders `template` for problem `id`, optionally titled `name`.
+pub fn render_template(template: &str, id: &str, name: Option<&str>) -> String {
+    let escape = |value: &str| {
+        let quoted = format!("{:?}", value);
+        quoted[1..quoted.len() - 1].to_string()
+    };
+    let rendered = template
+        .replace("{{ID}}", &escape(id))
+        .replace("{{PROBLEM}}", &escape(name.unwrap_or_default()));
+
+    let rendered = set_const(&rendered, "_ID", Some(id));
+    set_const(&rendered, "_PROBLEM", name)
+}
+
+/// Sets the value of a `const NAME: &str` item, adding it after the imports
+/// when missing. `None` removes the const.
+pub fn set_const(source: &str, name: &str, value: Option<&str>) -> String {
+    let pattern = Regex::new(&format!(
+        r#"(?m)^([ \t]*(?:pub(?:\([^)]*\))?\s+)?const\s+{}\s*:\s*&\s*(?:'static\s+)?str\s*=\s*)"(?:[^"\\]|\\.)*"\s*;[ \t]*\n?"#,
+        regex::escape(name)
+    ))
+    .unwrap();
+
+    let Some(value) = value else {
+        return pattern.replace(source, "").to_string();
+    };
+    let literal = format!("{:?}", value);
+
+    if pattern.is_match(source) {
+        return pattern
+            .replace(source, |caps: &regex::Captures| format!("{}{};\n", &caps[1], literal))
+            .to_string();
+    }
+
+    // Insert after the last top-level `use` or `const` line, or at the top.
+    let line_at = |end: usize| {
+        let start = source[..end - 1].rfind('\n').map_or(0, |i| i + 1);
+        &source[start..end]
+    };
+    let insert_at = source
+        .match_indices('\n')
+        .map(|(i, _)| i + 1)
+        .rfind(|&end| line_at(end).starts_with("use ") || line_at(end).starts_with("const "));
+
+    let declaration = format!("const {}: &str = {};\n", name, literal);
+    match insert_at {
+        Some(at) if line_at(at).starts_with("use ") => {
+            format!("{}\n{}{}", &source[..at], declaration, &source[at..])
+        }
+        Some(at) => format!("{}{}{}", &source[..at], declaration, &source[at..]),
+        None => format!("{}\n{}", declaration, source),
+    }
+}
+
+/// Moves `file` into `archive_dir` as `<id>.rs`, taking the ID from its `_ID`
+/// const or its file name. Returns the archive path, or `None` if there was
+/// nothing to archive.
+pub fn archive_solution(file: &Path, archive_dir: &Path) -> Result<Option<PathBuf>, Box<dyn std::error::Error>> {
+    let Ok(content) = fs::read_to_string(file) else {
+        return Ok(None);
+    };
+    if content.trim().is_empty() {
+        return Ok(None);
+    }
+
+    let id = syn::parse_str::<syn::File>(&content)
+        .ok()
+        .and_then(|ast| CodeBundler::extract_id(&ast))
+        .or_else(|| file.file_stem().map(|s| s.to_string_lossy().to_string()))
+        .unwrap_or_else(|| "solution".to_string());
+
+    fs::create_dir_all(archive_dir)?;
+    let mut target = archive_dir.join(format!("{}.rs", id));
+    if target.exists() {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_secs();
+        target = archive_dir.join(format!("{}_{}.rs", id, timestamp));
+    }
+
+    fs::write(&target, content)?;
+    Ok(Some(target))
+}
+
+/// Archives whatever is at `target` and writes a fresh solution there.
+pub fn new_problem(
+    target: &Path,
+    id: &str,
+    name: Option<&str>,
+    options: &ScaffoldOptions,
+) -> Result<Option<PathBuf>, Box<dyn std::error::Error>> {
+    let template = match &options.template {
+        Some(path) => fs::read_to_string(path)
+            .map_err(|e| format!("template {}: {}", path.display(), e))?,
+        None => DEFAULT_TEMPLATE.to_string(),
+    };
+
+    let archived = if options.archive {
+        archive_solution(target, &options.archive_dir)?
+    } else {
+        None
+    };
+
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(target, render_template(&template, id, name))?;
+    Ok(archived)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn default_template_fills_consts() {
+        let source = render_template(DEFAULT_TEMPLATE, "1234D2", Some("D2. Magic \"Powder\""));
+        let ast: syn::File = syn::parse_str(&source).unwrap();
+
+        assert_eq!(CodeBundler::extract_id(&ast).as_deref(), Some("1234D2"));
+        assert_eq!(
+            CodeBundler::extract_problem_name(&ast).as_deref(),
+            Some("D2. Magic \"Powder\"")
+        );
+    }
+
+    #[test]
+    fn missing_name_drops_problem_const() {
+        let source = render_template(DEFAULT_TEMPLATE, "A", None);
+        assert!(!source.contains("_PROBLEM"));
+        assert!(source.contains("const _ID: &str = \"A\";"));
+    }
+
+    #[test]
+    fn adds_consts_missing_from_custom_template() {
+        let template = "use std::fmt::Write;\nuse cp_lib::io::scanner::Scanner;\n\nfn main() {}\n";
+        let source = render_template(template, "B", Some("B. Game"));
+
+        assert_eq!(
+            source,
+            "use std::fmt::Write;\nuse cp_lib::io::scanner::Scanner;\n\n\
+             const _ID: &str = \"B\";\n\
+             const _PROBLEM: &str = \"B. Game\";\n\nfn main() {}\n"
+        );
+    }
+
+    #[test]
+    fn archives_previous_solution_by_id() {
+        let dir = tempdir().unwrap();
+        let main_file = dir.path().join("main.rs");
+        let archive = dir.path().join("archive");
+        fs::write(&main_file, "const _ID: &str = \"1900A\";\nfn main() {}\n").unwrap();
+
+        let options = ScaffoldOptions {
+            template: None,
+            archive_dir: archive.clone(),
+            archive: true,
+        };
+        let archived = new_problem(&main_file, "1900B", None, &options).unwrap();
+
+        assert_eq!(archived, Some(archive.join("1900A.rs")));
+        assert!(fs::read_to_string(archive.join("1900A.rs")).unwrap().contains("1900A"));
+        assert!(fs::read_to_string(&main_file).unwrap().contains("\"1900B\""));
+
+        // A second archive with the same ID must not overwrite the first.
+        fs::write(&main_file, "const _ID: &str = \"1900A\";\nfn main() { }\n").unwrap();
+        let second = new_problem(&main_file, "1900C", None, &options).unwrap().unwrap();
+        assert_ne!(second, archive.join("1900A.rs"));
+    }
+}
@@ -0,0 +1,322 @@
+/*
+ * Stress testing: solution vs. brute force on generated inputs
+ */
+
+use crate::checker::Checker;
+use crate::limits::Limits;
+use crate::runner::{self, RunOutput};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Compiled programs taking part in a stress run.
+pub struct StressBinaries {
+    pub solution: PathBuf,
+    pub brute: PathBuf,
+    /// Called with the seed as its only argument; prints one input to stdout.
+    pub generator: PathBuf,
+}
+
+#[derive(Debug, Clone)]
+pub struct StressOptions {
+    /// First seed; run `i` uses `seed + i`, wrapping around at `u64::MAX`.
+    pub seed: u64,
+    /// Stop after this many runs.
+    pub iterations: Option<u64>,
+    /// Stop once this much time has passed.
+    pub time_budget: Option<Duration>,
+    /// Number of runs executed in parallel.
+    pub jobs: usize,
+    /// Judges the solution's output against the brute force's.
+    pub checker: Checker,
+    /// Limits for each run of the generator, the solution and the brute force,
+    /// so a run that never finishes is reported instead of hanging the stress test.
+    pub limits: Limits,
+}
+
+/// Time limit per run when the judge profile sets none.
+pub const DEFAULT_RUN_TIME_LIMIT: Duration = Duration::from_secs(3);
+
+impl Default for StressOptions {
+    fn default() -> Self {
+        Self {
+            seed: 1,
+            iterations: None,
+            time_budget: Some(Duration::from_secs(60)),
+            jobs: 1,
+            checker: Checker::default(),
+            limits: Limits {
+                time: Some(DEFAULT_RUN_TIME_LIMIT),
+                memory: None,
+            },
+        }
+    }
+}
+
+/// A seed on which the solution and the brute force disagree.
+#[derive(Debug, Clone)]
+pub struct StressFailure {
+    pub seed: u64,
+    pub input: String,
+    pub solution_output: String,
+    pub brute_output: String,
+    pub reason: String,
+}
+
+impl StressFailure {
+    /// Writes `input.txt`, `solution.txt`, `brute.txt` and `seed.txt` into `dir`.
+    pub fn save(&self, dir: &Path) -> io::Result<()> {
+        fs::create_dir_all(dir)?;
+        fs::write(dir.join("input.txt"), &self.input)?;
+        fs::write(dir.join("solution.txt"), &self.solution_output)?;
+        fs::write(dir.join("brute.txt"), &self.brute_output)?;
+        fs::write(dir.join("seed.txt"), format!("{}\n", self.seed))
+    }
+}
+
+#[derive(Debug)]
+pub enum StressOutcome {
+    /// No mismatch within the iteration or time budget.
+    Passed { runs: u64 },
+    Failed(StressFailure),
+}
+
+/// Runs the solution and the brute force on one generated input.
+pub fn run_once(
+    binaries: &StressBinaries,
+    seed: u64,
+    checker: &Checker,
+    limits: &Limits,
+) -> io::Result<Option<StressFailure>> {
+    let generated = runner::run_limited(&binaries.generator, &[seed.to_string()], &[], limits)?;
+    if generated.timed_out() {
+        return Err(io::Error::other(format!("generator timed out on seed {}", seed)));
+    }
+    if !generated.status.success() {
+        return Err(io::Error::other(format!(
+            "generator failed on seed {}: {}",
+            seed,
+            runner::error_summary(&generated.stderr)
+        )));
+    }
+
+    let input = generated.stdout;
+    let solution = runner::run_limited(&binaries.solution, &[], input.as_bytes(), limits)?;
+    let brute = runner::run_limited(&binaries.brute, &[], input.as_bytes(), limits)?;
+
+    let crashed = |name: &str, run: &RunOutput| {
+        format!("{} crashed ({}): {}", name, run.status, runner::error_summary(&run.stderr))
+    };
+    let reason = if brute.timed_out() {
+        // A broken brute force is reported too, its output is not a reference.
+        Some("brute force timed out".to_string())
+    } else if !brute.status.success() {
+        Some(crashed("brute force", &brute))
+    } else if solution.timed_out() {
+        Some("solution timed out".to_string())
+    } else if !solution.status.success() {
+        Some(crashed("solution", &solution))
+    } else {
+        checker.check(&input, &brute.stdout, &solution.stdout)?.err()
+    };
+
+    Ok(reason.map(|reason| StressFailure {
+        seed,
+        input,
+        solution_output: solution.stdout,
+        brute_output: brute.stdout,
+        reason,
+    }))
+}
+
+/// Runs seeds until a mismatch is found or a budget runs out. With several
+/// jobs the failure with the smallest seed among those found is reported.
+pub fn stress(binaries: &StressBinaries, options: &StressOptions) -> io::Result<StressOutcome> {
+    let deadline = options.time_budget.map(|budget| Instant::now() + budget);
+    let next_run = AtomicU64::new(0);
+    let stop = AtomicBool::new(false);
+    let completed = AtomicU64::new(0);
+    let failure: Mutex<Option<StressFailure>> = Mutex::new(None);
+    let error: Mutex<Option<io::Error>> = Mutex::new(None);
+
+    std::thread::scope(|scope| {
+        for _ in 0..options.jobs.max(1) {
+            scope.spawn(|| {
+                while !stop.load(Ordering::Relaxed) {
+                    if deadline.is_some_and(|d| Instant::now() >= d) {
+                        break;
+                    }
+                    let run = next_run.fetch_add(1, Ordering::Relaxed);
+                    if options.iterations.is_some_and(|limit| run >= limit) {
+                        break;
+                    }
+
+                    match run_once(binaries, options.seed.wrapping_add(run), &options.checker, &options.limits) {
+                        Ok(None) => {
+                            completed.fetch_add(1, Ordering::Relaxed);
+                        }
+                        Ok(Some(found)) => {
+                            stop.store(true, Ordering::Relaxed);
+                            let mut slot = failure.lock().unwrap();
+                            if slot.as_ref().is_none_or(|f| found.seed < f.seed) {
+                                *slot = Some(found);
+                            }
+                        }
+                        Err(e) => {
+                            stop.store(true, Ordering::Relaxed);
+                            error.lock().unwrap().get_or_insert(e);
+                        }
+                    }
+                }
+            });
+        }
+    });
+
+    if let Some(e) = error.into_inner().unwrap() {
+        return Err(e);
+    }
+    Ok(match failure.into_inner().unwrap() {
+        Some(found) => StressOutcome::Failed(found),
+        None => StressOutcome::Passed {
+            runs: completed.into_inner(),
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project_config::JudgeProfile;
+    use tempfile::tempdir;
+
+    fn build(dir: &Path, name: &str, source: &str) -> PathBuf {
+        let path = dir.join(format!("{}.rs", name));
+        fs::write(&path, source).unwrap();
+        let binary = dir.join(name);
+        runner::compile(&path, &binary, &JudgeProfile::default()).unwrap();
+        binary
+    }
+
+    const READ_N: &str = "let mut s = String::new(); \
+        std::io::Read::read_to_string(&mut std::io::stdin(), &mut s).unwrap(); \
+        let n: u64 = s.trim().parse().unwrap();";
+
+    #[test]
+    fn finds_first_failing_seed() {
+        let dir = tempdir().unwrap();
+        let generator = build(
+            dir.path(),
+            "gen",
+            "fn main() { let seed: u64 = std::env::args().nth(1).unwrap().parse().unwrap(); \
+             println!(\"{}\", seed % 10); }",
+        );
+        let brute = build(dir.path(), "brute", &format!("fn main() {{ {} println!(\"{{}}\", n * n); }}", READ_N));
+        // Wrong for n = 7 only.
+        let solution = build(
+            dir.path(),
+            "sol",
+            &format!("fn main() {{ {} println!(\"{{}}\", if n == 7 {{ 0 }} else {{ n * n }}); }}", READ_N),
+        );
+        let binaries = StressBinaries { solution, brute, generator };
+
+        let options = StressOptions {
+            seed: 1,
+            iterations: Some(40),
+            time_budget: None,
+            jobs: 3,
+            ..Default::default()
+        };
+        let StressOutcome::Failed(failure) = stress(&binaries, &options).unwrap() else {
+            panic!("expected a mismatch");
+        };
+        assert_eq!(failure.seed % 10, 7);
+        assert_eq!(failure.input, "7\n");
+        assert_eq!(failure.brute_output, "49\n");
+        assert_eq!(failure.reason, "token 1 (line 1): expected `49`, found `0`");
+
+        failure.save(&dir.path().join("failure")).unwrap();
+        assert_eq!(fs::read_to_string(dir.path().join("failure/input.txt")).unwrap(), "7\n");
+
+        let passing = StressOptions {
+            iterations: Some(5),
+            ..options.clone()
+        };
+        assert!(matches!(
+            stress(&binaries, &passing).unwrap(),
+            StressOutcome::Passed { runs: 5 }
+        ));
+    }
+
+    #[test]
+    fn reports_a_solution_that_never_returns() {
+        let dir = tempdir().unwrap();
+        let generator = build(
+            dir.path(),
+            "gen",
+            "fn main() { let seed: u64 = std::env::args().nth(1).unwrap().parse().unwrap(); println!(\"{}\", seed); }",
+        );
+        let brute = build(dir.path(), "brute", &format!("fn main() {{ {} println!(\"{{}}\", n); }}", READ_N));
+        let solution = build(
+            dir.path(),
+            "sol",
+            &format!("fn main() {{ {} if n == 2 {{ loop {{}} }} println!(\"{{}}\", n); }}", READ_N),
+        );
+        let binaries = StressBinaries { solution, brute, generator };
+
+        let options = StressOptions {
+            seed: 1,
+            iterations: Some(3),
+            time_budget: None,
+            jobs: 1,
+            limits: Limits {
+                time: Some(Duration::from_millis(200)),
+                memory: None,
+            },
+            ..Default::default()
+        };
+        let StressOutcome::Failed(failure) = stress(&binaries, &options).unwrap() else {
+            panic!("expected a timeout");
+        };
+        assert_eq!(failure.seed, 2);
+        assert_eq!(failure.input, "2\n");
+        assert_eq!(failure.reason, "solution timed out");
+
+        failure.save(&dir.path().join("failure")).unwrap();
+        assert_eq!(fs::read_to_string(dir.path().join("failure/seed.txt")).unwrap(), "2\n");
+    }
+
+    #[test]
+    fn reports_a_generator_that_never_returns() {
+        let dir = tempdir().unwrap();
+        // Seeds wrap around, so the second run gets seed 0.
+        let generator = build(
+            dir.path(),
+            "gen",
+            "fn main() { let seed: u64 = std::env::args().nth(1).unwrap().parse().unwrap(); \
+             if seed == 0 { loop {} } println!(\"{}\", seed % 10); }",
+        );
+        let brute = build(dir.path(), "brute", &format!("fn main() {{ {} println!(\"{{}}\", n); }}", READ_N));
+        let binaries = StressBinaries {
+            solution: brute.clone(),
+            brute,
+            generator,
+        };
+
+        let options = StressOptions {
+            seed: u64::MAX,
+            iterations: Some(2),
+            time_budget: None,
+            jobs: 1,
+            limits: Limits {
+                time: Some(Duration::from_millis(200)),
+                memory: None,
+            },
+            ..Default::default()
+        };
+        let error = stress(&binaries, &options).unwrap_err();
+        assert_eq!(error.to_string(), "generator timed out on seed 0");
+    }
+}
@@ -1,4 +1,14 @@
-use cp_lib::io::scanner::Scanner;
+use cp_lib::io::scanner::{Scanner, Usize1};
+use cp_lib::readable;
+
+readable! {
+    #[derive(Debug)]
+    struct Query {
+        left: Usize1,
+        right: usize,
+        add: i64,
+    }
+}
 
 fn main() {
     // Example usage of Scanner with custom string input
@@ -48,4 +58,20 @@ fn main() {
         let sum: i32 = array.iter().sum();
         println!("Test case {}: array = {:?}, sum = {}", case, array, sum);
     }
-}
\ No newline at end of file
+
+    println!("\n--- Tuples, 1-based indices and structs ---");
+
+    let graph_input = "4 3\n1 2 5\n2 3 7\n3 4 1\n1 4\n2\n1 3 10\n2 4 -2".to_string();
+    let mut graph_scanner = Scanner::from_string(graph_input);
+
+    let (n, m) = graph_scanner.read::<(usize, usize)>();
+    let edges = graph_scanner.read_vec::<(usize, usize, i64)>(m);
+    println!("{} vertices, edges: {:?}", n, edges);
+
+    let (s, t) = graph_scanner.read::<(Usize1, Usize1)>();
+    println!("Query path from {} to {} (0-based)", s, t);
+
+    let q: usize = graph_scanner.next();
+    let queries = graph_scanner.read_vec::<Query>(q);
+    println!("Range updates over [left, right): {:?}", queries);
+}
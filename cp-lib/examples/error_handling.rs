@@ -58,5 +58,25 @@ fn main() {
     let words: Vec<String> = scanner4.dump(n4b);
     println!("   Words: {:?}", words);
 
+    // Example 5: Errors as values instead of panics
+    println!("\n5. Non-panicking reads with scan and try_dump:");
+    let input5 = "3\n1 abc 3".to_string();
+    let mut scanner5 = Scanner::from_string(input5);
+    let n5: usize = scanner5.next();
+
+    match scanner5.try_dump::<i32>(n5) {
+        Ok(values) => println!("   Read {:?} (unexpected)", values),
+        Err(e) => println!("   ✓ {}", e),
+    }
+
+    // Example 6: Reading until the end of input
+    println!("\n6. Reading until the end of input with try_next:");
+    let mut scanner6 = Scanner::from_string("4 8\n15\n16 23 42\n".to_string());
+    let mut sum = 0;
+    while let Some(x) = scanner6.try_next::<i32>() {
+        sum += x;
+    }
+    println!("   Sum: {}", sum);
+
     println!("\n✓ All examples completed successfully!");
 }
\ No newline at end of file
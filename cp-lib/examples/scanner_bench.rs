@@ -0,0 +1,26 @@
+use cp_lib::io::fast_scanner::FastScanner;
+use cp_lib::io::scanner::Scanner;
+
+// Reads `n` followed by `n` integers from stdin and prints their sum.
+// Usage: scanner_bench [scanner|fast] < input.txt
+fn main() {
+    let mode = std::env::args().nth(1).unwrap_or_else(|| "fast".to_string());
+    let start = std::time::Instant::now();
+
+    let sum: i64 = match mode.as_str() {
+        "scanner" => {
+            let mut sc = Scanner::new();
+            let n: usize = sc.next();
+            sc.dump::<i64>(n).iter().sum()
+        }
+        "fast" => {
+            let mut sc = FastScanner::new();
+            let n: usize = sc.next();
+            sc.dump::<i64>(n).iter().sum()
+        }
+        other => panic!("unknown mode '{}', expected scanner or fast", other),
+    };
+
+    println!("{}", sum);
+    eprintln!("{}: {:?}", mode, start.elapsed());
+}
@@ -0,0 +1,33 @@
+use cp_lib::io::writer::Writer;
+use std::io::Write;
+
+// Prints the integers 0..n times a large odd constant, one per line.
+// Usage: writer_bench [println|lock|writer] [n] > /dev/null
+fn main() {
+    let mode = std::env::args().nth(1).unwrap_or_else(|| "writer".to_string());
+    let n: u64 = std::env::args().nth(2).map_or(1_000_000, |n| n.parse().unwrap());
+    let start = std::time::Instant::now();
+
+    match mode.as_str() {
+        "println" => {
+            for i in 0..n {
+                println!("{}", i.wrapping_mul(0x9E3779B97F4A7C15) as i64);
+            }
+        }
+        "lock" => {
+            let mut out = std::io::BufWriter::new(std::io::stdout().lock());
+            for i in 0..n {
+                writeln!(out, "{}", i.wrapping_mul(0x9E3779B97F4A7C15) as i64).unwrap();
+            }
+        }
+        "writer" => {
+            let mut out = Writer::new();
+            for i in 0..n {
+                out.println(i.wrapping_mul(0x9E3779B97F4A7C15) as i64);
+            }
+        }
+        other => panic!("unknown mode '{}', expected println, lock or writer", other),
+    }
+
+    eprintln!("{}: {:?}", mode, start.elapsed());
+}
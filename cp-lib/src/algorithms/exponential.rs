@@ -26,3 +26,97 @@ where
     res
 }
 
+/// Integer types the modular functions work with. Products are widened to
+/// 128 bits, so any modulus the type can hold is safe, including 64-bit ones.
+/// Results are always in `[0, m)`, for signed types too. `i128` and `u128`
+/// are not implemented, since their products would need 256 bits.
+pub trait Modular: Copy {
+    fn to_i128(self) -> i128;
+    /// Narrows a value known to be in `[0, m)`.
+    fn from_i128(value: i128) -> Self;
+}
+
+macro_rules! impl_modular {
+    ($($t:ty),*) => {$(
+        impl Modular for $t {
+            #[inline]
+            fn to_i128(self) -> i128 {
+                self as i128
+            }
+
+            #[inline]
+            fn from_i128(value: i128) -> Self {
+                value as $t
+            }
+        }
+    )*};
+}
+
+impl_modular!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+/// `(a * b) mod m` without overflow, for any `Modular` type (up to 64 bits).
+#[inline]
+pub fn mul_mod<T: Modular>(a: T, b: T, m: T) -> T {
+    let m = m.to_i128();
+    debug_assert!(m > 0, "modulus must be positive");
+    let (a, b) = (a.to_i128().rem_euclid(m) as u128, b.to_i128().rem_euclid(m) as u128);
+    T::from_i128(((a * b) % m as u128) as i128)
+}
+
+/// `base^exp mod m` by binary exponentiation.
+///
+/// - `base`: may be negative or larger than `m`
+/// - `exp`: any unsigned exponent, as in `binpow`
+/// - returns: a value in `[0, m)`; `0` when `m` is 1, `1 % m` when `exp` is 0
+#[inline]
+pub fn pow_mod<T, E>(base: T, mut exp: E, m: T) -> T
+where
+    T: Modular,
+    E: Copy + PartialEq + From<u8> + BitAnd<Output = E> + ShrAssign<u8>,
+{
+    let one = E::from(1u8);
+    let zero = E::from(0u8);
+    let mut base = T::from_i128(base.to_i128().rem_euclid(m.to_i128()));
+    let mut res = T::from_i128(1 % m.to_i128());
+
+    while exp != zero {
+        if (exp & one) != zero {
+            res = mul_mod(res, base, m);
+        }
+        base = mul_mod(base, base, m);
+        exp >>= 1u8;
+    }
+    res
+}
+
+/// Inverse of `a` modulo a prime `p` by Fermat's little theorem:
+/// `a^(p-2) mod p`. `a` must not be a multiple of `p`; for other moduli use
+/// `mod_inverse`.
+#[inline]
+pub fn mod_inverse_prime<T: Modular>(a: T, p: T) -> T {
+    let p_wide = p.to_i128();
+    if p_wide == 1 {
+        return T::from_i128(0);
+    }
+    debug_assert!(a.to_i128().rem_euclid(p_wide) != 0, "0 has no inverse");
+    pow_mod(a, (p_wide - 2) as u128, p)
+}
+
+/// Inverse of `a` modulo any `m` by the extended Euclidean algorithm, or
+/// `None` when `a` and `m` are not coprime.
+pub fn mod_inverse<T: Modular>(a: T, m: T) -> Option<T> {
+    let m = m.to_i128();
+    debug_assert!(m > 0, "modulus must be positive");
+    // Invariant: old_s * a ≡ old_r and s * a ≡ r (mod m).
+    let (mut old_r, mut r) = (a.to_i128().rem_euclid(m), m);
+    let (mut old_s, mut s) = (1i128, 0i128);
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_s, s) = (s, old_s - q * s);
+    }
+    if old_r != 1 {
+        return None;
+    }
+    Some(T::from_i128(old_s.rem_euclid(m)))
+}
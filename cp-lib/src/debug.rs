@@ -0,0 +1,145 @@
+//! `debug!` prints variables to stderr in local builds: debug builds, or any
+//! build with the `local` feature. Otherwise it expands to nothing, and the
+//! bundler drops its calls along with everything below, so none of it
+//! reaches a submission.
+
+#[cfg(any(feature = "local", debug_assertions))]
+use std::fmt::Debug;
+
+/// A value being printed by `debug!`. 2D vectors go through `Table` and are
+/// printed as tables; anything else goes through `Plain`, which is only
+/// reached by autoref when `Table` does not apply.
+#[cfg(any(feature = "local", debug_assertions))]
+pub struct Wrap<'a, T: ?Sized>(pub &'a T);
+
+#[cfg(any(feature = "local", debug_assertions))]
+pub trait Table {
+    fn debug_string(&self) -> String;
+}
+
+#[cfg(any(feature = "local", debug_assertions))]
+pub trait Plain {
+    fn debug_string(&self) -> String;
+}
+
+#[cfg(any(feature = "local", debug_assertions))]
+impl<T: Debug> Table for Wrap<'_, Vec<Vec<T>>> {
+    fn debug_string(&self) -> String {
+        table(self.0)
+    }
+}
+
+#[cfg(any(feature = "local", debug_assertions))]
+impl<T: Debug> Table for Wrap<'_, &Vec<Vec<T>>> {
+    fn debug_string(&self) -> String {
+        table(self.0)
+    }
+}
+
+#[cfg(any(feature = "local", debug_assertions))]
+impl<T: Debug + ?Sized> Plain for &Wrap<'_, T> {
+    fn debug_string(&self) -> String {
+        format!("{:?}", self.0)
+    }
+}
+
+/// Rows on separate lines, columns right-aligned. Chars are shown without
+/// quotes, so a `#.#` grid reads as `# . #`.
+#[cfg(any(feature = "local", debug_assertions))]
+pub fn table<T: Debug>(rows: &[Vec<T>]) -> String {
+    let cells: Vec<Vec<String>> = rows
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|cell| {
+                    let text = format!("{:?}", cell);
+                    match text.strip_prefix('\'').and_then(|t| t.strip_suffix('\'')) {
+                        Some(inner) if !inner.is_empty() => inner.to_string(),
+                        _ => text,
+                    }
+                })
+                .collect()
+        })
+        .collect();
+
+    let columns = cells.iter().map(Vec::len).max().unwrap_or(0);
+    let widths: Vec<usize> = (0..columns)
+        .map(|j| cells.iter().filter_map(|row| row.get(j)).map(|cell| cell.chars().count()).max().unwrap_or(0))
+        .collect();
+    cells
+        .iter()
+        .map(|row| {
+            row.iter()
+                .zip(&widths)
+                .map(|(cell, &width)| format!("{:>width$}", cell, width = width))
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The text `debug!` prints: `[file:line] a = 1, b = [2, 3]`, or with a
+/// multi-line value, one `name =` per line and tables indented below it.
+#[cfg(any(feature = "local", debug_assertions))]
+pub fn format_entries(file: &str, line: u32, entries: &[(&str, String)]) -> String {
+    let mut out = format!("[{}:{}]", file, line);
+    if entries.iter().all(|(_, value)| !value.contains('\n')) {
+        let joined: Vec<String> = entries.iter().map(|(name, value)| format!("{} = {}", name, value)).collect();
+        out.push(' ');
+        out.push_str(&joined.join(", "));
+        return out;
+    }
+    for (name, value) in entries {
+        if value.contains('\n') {
+            out.push_str(&format!("\n{} =", name));
+            for row in value.lines() {
+                out.push_str("\n  ");
+                out.push_str(row);
+            }
+        } else {
+            out.push_str(&format!("\n{} = {}", name, value));
+        }
+    }
+    out
+}
+
+/// Prints each expression with its source text to stderr:
+/// `debug!(n, a, grid, mask => bin)`. 2D vectors are printed as tables and
+/// `=> bin` prints an integer in binary.
+#[cfg(any(feature = "local", debug_assertions))]
+#[macro_export]
+macro_rules! debug {
+    ($($value:expr $(=> $format:ident)?),* $(,)?) => {
+        eprintln!(
+            "{}",
+            $crate::debug::format_entries(
+                file!(),
+                line!(),
+                &[$((stringify!($value), $crate::__debug_value!($value $(, $format)?))),*],
+            )
+        )
+    };
+}
+
+#[cfg(not(any(feature = "local", debug_assertions)))]
+#[macro_export]
+macro_rules! debug {
+    ($($tokens:tt)*) => {};
+}
+
+#[cfg(any(feature = "local", debug_assertions))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __debug_value {
+    ($value:expr) => {{
+        #[allow(unused_imports)]
+        use $crate::debug::Plain as _;
+        #[allow(unused_imports)]
+        use $crate::debug::Table as _;
+        (&$crate::debug::Wrap(&$value)).debug_string()
+    }};
+    ($value:expr, bin) => {
+        format!("{:#b}", $value)
+    };
+}
@@ -0,0 +1,236 @@
+use std::io::{self, Cursor, Read};
+
+const BUFFER_SIZE: usize = 1 << 16;
+
+/// Types a `FastScanner` can build straight from the bytes of a token.
+///
+/// Integers are parsed from the bytes without going through `str`; other
+/// types go through `FromStr`. Use `FastScanner::parse` for any other
+/// `FromStr` type.
+pub trait FromToken: Sized {
+    fn from_token(token: &[u8]) -> Self;
+}
+
+#[cold]
+fn parse_failure<T>(token: &[u8]) -> ! {
+    panic!(
+        "Failed to parse '{}' as type {}",
+        String::from_utf8_lossy(token),
+        std::any::type_name::<T>()
+    )
+}
+
+macro_rules! impl_from_token_unsigned {
+    ($($t:ty),*) => {$(
+        impl FromToken for $t {
+            #[inline]
+            fn from_token(token: &[u8]) -> Self {
+                let digits = token.strip_prefix(b"+").unwrap_or(token);
+                if digits.is_empty() {
+                    parse_failure::<$t>(token);
+                }
+                let mut value: $t = 0;
+                for &b in digits {
+                    let digit = b.wrapping_sub(b'0');
+                    if digit > 9 {
+                        parse_failure::<$t>(token);
+                    }
+                    value = match value.checked_mul(10).and_then(|v| v.checked_add(digit as $t)) {
+                        Some(value) => value,
+                        None => parse_failure::<$t>(token),
+                    };
+                }
+                value
+            }
+        }
+    )*};
+}
+
+macro_rules! impl_from_token_signed {
+    ($($t:ty),*) => {$(
+        impl FromToken for $t {
+            #[inline]
+            fn from_token(token: &[u8]) -> Self {
+                let (negative, digits) = match token.split_first() {
+                    Some((b'-', rest)) => (true, rest),
+                    Some((b'+', rest)) => (false, rest),
+                    _ => (false, token),
+                };
+                if digits.is_empty() {
+                    parse_failure::<$t>(token);
+                }
+                // Accumulating negative values reaches MIN without overflow.
+                let mut value: $t = 0;
+                for &b in digits {
+                    let digit = b.wrapping_sub(b'0');
+                    if digit > 9 {
+                        parse_failure::<$t>(token);
+                    }
+                    let shifted = value.checked_mul(10);
+                    let next = if negative {
+                        shifted.and_then(|v| v.checked_sub(digit as $t))
+                    } else {
+                        shifted.and_then(|v| v.checked_add(digit as $t))
+                    };
+                    value = match next {
+                        Some(value) => value,
+                        None => parse_failure::<$t>(token),
+                    };
+                }
+                value
+            }
+        }
+    )*};
+}
+
+impl_from_token_unsigned!(u8, u16, u32, u64, u128, usize);
+impl_from_token_signed!(i8, i16, i32, i64, i128, isize);
+
+macro_rules! impl_from_token_via_str {
+    ($($t:ty),*) => {$(
+        impl FromToken for $t {
+            fn from_token(token: &[u8]) -> Self {
+                std::str::from_utf8(token)
+                    .ok()
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or_else(|| parse_failure::<$t>(token))
+            }
+        }
+    )*};
+}
+
+impl_from_token_via_str!(f32, f64, bool, char, String);
+
+impl FromToken for Vec<u8> {
+    fn from_token(token: &[u8]) -> Self {
+        token.to_vec()
+    }
+}
+
+/// Byte-level scanner: reads input in large chunks and parses tokens in
+/// place, without allocating a `String` per token.
+///
+/// Chunks are read as they are needed, so it also works for interactive
+/// problems as long as every response ends with a newline.
+pub struct FastScanner<R: Read> {
+    reader: R,
+    buf: Vec<u8>,
+    pos: usize,
+    len: usize,
+}
+
+impl FastScanner<io::StdinLock<'static>> {
+    pub fn new() -> Self {
+        Self::from_reader(io::stdin().lock())
+    }
+}
+
+impl Default for FastScanner<io::StdinLock<'static>> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R: Read> FastScanner<R> {
+    pub fn from_reader(reader: R) -> Self {
+        Self {
+            reader,
+            buf: vec![0; BUFFER_SIZE],
+            pos: 0,
+            len: 0,
+        }
+    }
+
+    /// Moves the bytes from `keep` on to the front of the buffer and reads
+    /// more after them, growing the buffer when a token fills all of it.
+    /// Returns false at end of input.
+    fn fill(&mut self, keep: usize) -> bool {
+        self.buf.copy_within(keep..self.len, 0);
+        self.len -= keep;
+        self.pos -= keep;
+        if self.len == self.buf.len() {
+            self.buf.resize(self.buf.len() * 2, 0);
+        }
+        loop {
+            match self.reader.read(&mut self.buf[self.len..]) {
+                Ok(0) => return false,
+                Ok(n) => {
+                    self.len += n;
+                    return true;
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => panic!("Error reading input: {}", e),
+            }
+        }
+    }
+
+    /// The next whitespace-separated token, or `None` at end of input.
+    pub fn token(&mut self) -> Option<&[u8]> {
+        loop {
+            while self.pos < self.len && self.buf[self.pos].is_ascii_whitespace() {
+                self.pos += 1;
+            }
+            if self.pos < self.len {
+                break;
+            }
+            if !self.fill(self.pos) {
+                return None;
+            }
+        }
+
+        let mut start = self.pos;
+        loop {
+            while self.pos < self.len && !self.buf[self.pos].is_ascii_whitespace() {
+                self.pos += 1;
+            }
+            if self.pos < self.len {
+                break;
+            }
+            // The token may continue in the next chunk.
+            let more = self.fill(start);
+            start = 0;
+            if !more {
+                break;
+            }
+        }
+        Some(&self.buf[start..self.pos])
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn next<T: FromToken>(&mut self) -> T {
+        match self.token() {
+            Some(token) => T::from_token(token),
+            None => panic!("Unexpected end of input"),
+        }
+    }
+
+    /// Reads a token of any `FromStr` type.
+    pub fn parse<T: std::str::FromStr>(&mut self) -> T {
+        let token = self.token().unwrap_or_else(|| panic!("Unexpected end of input"));
+        std::str::from_utf8(token)
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or_else(|| parse_failure::<T>(token))
+    }
+
+    pub fn dump<T: FromToken>(&mut self, n: usize) -> Vec<T> {
+        let mut result = Vec::with_capacity(n);
+        for i in 0..n {
+            match self.token() {
+                Some(token) => result.push(T::from_token(token)),
+                None => panic!(
+                    "Unexpected end of input while reading entry {} of {} for dump operation",
+                    i + 1,
+                    n
+                ),
+            }
+        }
+        result
+    }
+}
+
+impl FastScanner<Cursor<Vec<u8>>> {
+    pub fn from_string(input: String) -> Self {
+        FastScanner::from_reader(Cursor::new(input.into_bytes()))
+    }
+}
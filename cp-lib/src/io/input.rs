@@ -0,0 +1,99 @@
+use crate::io::scanner::Scanner;
+use std::cell::RefCell;
+use std::io::{self, StdinLock};
+
+thread_local! {
+    static STDIN: RefCell<Scanner<StdinLock<'static>>> = RefCell::new(Scanner::from_reader(io::stdin().lock()));
+}
+
+/// Runs `f` with the stdin scanner `input!` reads from when no scanner is
+/// given. The scanner holds the stdin lock, so it cannot be mixed with a
+/// `Scanner::new()` on the same thread.
+pub fn with_stdin<T>(f: impl FnOnce(&mut Scanner<StdinLock<'static>>) -> T) -> T {
+    STDIN.with(|scanner| f(&mut scanner.borrow_mut()))
+}
+
+/// Declares variables read from input, in the style of proconio:
+///
+/// ```
+/// use cp_lib::input;
+/// use cp_lib::io::scanner::{Bytes, Scanner, Usize1};
+///
+/// let mut sc = Scanner::from_string("3\n1 2 3\n1 2\n2 3\nab#\n".to_string());
+/// input! {
+///     from sc,
+///     n: usize,
+///     a: [i64; n],
+///     edges: [(Usize1, Usize1); n - 1],
+///     s: Bytes,
+/// }
+/// assert_eq!(a, vec![1, 2, 3]);
+/// assert_eq!(edges, vec![(0, 1), (1, 2)]);
+/// assert_eq!(s, b"ab#");
+/// ```
+///
+/// A kind is any `Readable` type (`usize`, `char`, `Usize1`, `Chars`, a
+/// `readable!` struct), `[kind; len]` for a `Vec` of `len` values, `[kind]`
+/// for a `Vec` whose length comes first in the input, or a tuple of kinds.
+/// Kinds nest, as in `[[i64; w]; h]`; a grid of rows like `#.#` is
+/// `[Chars; h]`. Write `mut name: kind` for a mutable binding.
+///
+/// Without `from`, values are read with `with_stdin`.
+#[macro_export]
+macro_rules! input {
+    (from $scanner:expr, $($rest:tt)*) => {
+        let __scanner = &mut $scanner;
+        $crate::__input_inner!((__scanner); $($rest)*);
+    };
+    ($($rest:tt)*) => {
+        $crate::__input_inner!((); $($rest)*);
+    };
+}
+
+/// Splits `input!` into `name: kind` declarations.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __input_inner {
+    ($source:tt;) => {};
+    ($source:tt; mut $var:ident : $($rest:tt)*) => {
+        $crate::__input_inner!(@kind $source; [mut $var]; []; $($rest)*);
+    };
+    ($source:tt; $var:ident : $($rest:tt)*) => {
+        $crate::__input_inner!(@kind $source; [$var]; []; $($rest)*);
+    };
+    (@kind $source:tt; [$($var:tt)*]; [$($kind:tt)*]; , $($rest:tt)*) => {
+        let $($var)* = $crate::__input_read!($source; $($kind)*);
+        $crate::__input_inner!($source; $($rest)*);
+    };
+    (@kind $source:tt; [$($var:tt)*]; [$($kind:tt)*];) => {
+        let $($var)* = $crate::__input_read!($source; $($kind)*);
+    };
+    (@kind $source:tt; $var:tt; [$($kind:tt)*]; $next:tt $($rest:tt)*) => {
+        $crate::__input_inner!(@kind $source; $var; [$($kind)* $next]; $($rest)*);
+    };
+}
+
+/// Reads one value of an `input!` kind.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __input_read {
+    ($source:tt; [$kind:tt; $len:expr]) => {
+        (0..$len).map(|_| $crate::__input_read!($source; $kind)).collect::<Vec<_>>()
+    };
+    ($source:tt; [$kind:ty; $len:expr]) => {
+        (0..$len).map(|_| $crate::__input_read!($source; $kind)).collect::<Vec<_>>()
+    };
+    ($source:tt; [$kind:tt]) => {{
+        let len = $crate::__input_read!($source; usize);
+        (0..len).map(|_| $crate::__input_read!($source; $kind)).collect::<Vec<_>>()
+    }};
+    ($source:tt; ($($kind:tt),* $(,)?)) => {
+        ($($crate::__input_read!($source; $kind),)*)
+    };
+    ((); $kind:ty) => {
+        $crate::io::input::with_stdin(|scanner| scanner.read::<$kind>())
+    };
+    (($scanner:ident); $kind:ty) => {
+        $scanner.read::<$kind>()
+    };
+}
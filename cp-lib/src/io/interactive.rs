@@ -0,0 +1,174 @@
+use crate::io::scanner::{Readable, Scanner};
+use crate::io::writer::Writer;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::io::{self, BufRead, BufReader, Read, StdinLock, StdoutLock, Write};
+use std::rc::Rc;
+
+/// Reader and writer for interactive problems. Output is flushed after every
+/// line sent and before every read, so the judge never waits on a query
+/// sitting in a buffer. Responses are read a line at a time with `Scanner`.
+pub struct Interactor<R: BufRead, W: Write> {
+    scanner: Scanner<R>,
+    writer: Writer<W>,
+}
+
+impl Interactor<StdinLock<'static>, StdoutLock<'static>> {
+    pub fn new() -> Self {
+        Self::from_streams(io::stdin().lock(), io::stdout().lock())
+    }
+}
+
+impl Default for Interactor<StdinLock<'static>, StdoutLock<'static>> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R: BufRead, W: Write> Interactor<R, W> {
+    pub fn from_streams(reader: R, writer: W) -> Self {
+        Self {
+            scanner: Scanner::from_reader(reader),
+            writer: Writer::from_writer(writer),
+        }
+    }
+
+    /// Sends `line` and flushes it.
+    pub fn say(&mut self, line: impl Display) {
+        writeln!(self.writer, "{}", line).unwrap_or_else(|e| panic!("Error writing output: {}", e));
+        self.writer.flush();
+    }
+
+    /// Sends `query` and reads the response, e.g.
+    /// `io.ask::<i32>(format_args!("? {} {}", l, r))`.
+    pub fn ask<T: Readable>(&mut self, query: impl Display) -> T::Output {
+        self.say(query);
+        self.scanner.read::<T>()
+    }
+
+    /// Reads a `Readable` value, flushing anything written first.
+    pub fn read<T: Readable>(&mut self) -> T::Output {
+        self.writer.flush();
+        self.scanner.read::<T>()
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn next<T: std::str::FromStr>(&mut self) -> T {
+        self.writer.flush();
+        self.scanner.next()
+    }
+
+    /// The underlying scanner. Reading through it directly skips the flush.
+    pub fn scanner(&mut self) -> &mut Scanner<R> {
+        &mut self.scanner
+    }
+
+    /// The underlying writer, for output built up piece by piece; it is
+    /// flushed by the next `say`, `ask` or read.
+    pub fn writer(&mut self) -> &mut Writer<W> {
+        &mut self.writer
+    }
+}
+
+/// Reply of a `MockJudge` to one query line.
+type Respond = Box<dyn FnMut(&str) -> Option<String>>;
+
+struct JudgeState {
+    /// Bytes written by the solution since its last complete line.
+    partial: Vec<u8>,
+    /// Responses the solution has not read yet.
+    responses: VecDeque<u8>,
+    /// Complete lines the solution sent, in order.
+    queries: Vec<String>,
+    respond: Respond,
+}
+
+/// In-memory judge for testing interactive solutions. Each complete line the
+/// solution flushes is passed to `respond`, whose reply (if any) is what the
+/// solution reads next. Reading with nothing sent back is the end of input,
+/// so a query that was never flushed makes the solution fail instead of hang.
+pub struct MockJudge {
+    state: Rc<RefCell<JudgeState>>,
+}
+
+/// The solution's end of a `MockJudge`: what it reads.
+pub struct JudgeReader {
+    state: Rc<RefCell<JudgeState>>,
+}
+
+/// The solution's end of a `MockJudge`: what it writes.
+pub struct JudgeWriter {
+    state: Rc<RefCell<JudgeState>>,
+}
+
+pub type MockInteractor = Interactor<BufReader<JudgeReader>, JudgeWriter>;
+
+impl MockJudge {
+    /// A judge that first sends `initial` (e.g. `"5\n"` for `n`), then answers
+    /// each query line with `respond`.
+    pub fn new(initial: &str, respond: impl FnMut(&str) -> Option<String> + 'static) -> Self {
+        let state = JudgeState {
+            partial: Vec::new(),
+            responses: initial.bytes().collect(),
+            queries: Vec::new(),
+            respond: Box::new(respond),
+        };
+        Self {
+            state: Rc::new(RefCell::new(state)),
+        }
+    }
+
+    /// An interactor connected to this judge.
+    pub fn interactor(&self) -> MockInteractor {
+        let reader = JudgeReader {
+            state: Rc::clone(&self.state),
+        };
+        let writer = JudgeWriter {
+            state: Rc::clone(&self.state),
+        };
+        Interactor::from_streams(BufReader::new(reader), writer)
+    }
+
+    /// The lines the judge has received so far.
+    pub fn queries(&self) -> Vec<String> {
+        self.state.borrow().queries.clone()
+    }
+}
+
+impl Read for JudgeReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut state = self.state.borrow_mut();
+        let n = buf.len().min(state.responses.len());
+        for (slot, byte) in buf.iter_mut().zip(state.responses.drain(..n)) {
+            *slot = byte;
+        }
+        Ok(n)
+    }
+}
+
+impl Write for JudgeWriter {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        let mut state = self.state.borrow_mut();
+        for &byte in data {
+            if byte != b'\n' {
+                state.partial.push(byte);
+                continue;
+            }
+            let bytes = std::mem::take(&mut state.partial);
+            let line = String::from_utf8_lossy(&bytes).trim_end_matches('\r').to_string();
+            if let Some(mut response) = (state.respond)(&line) {
+                if !response.ends_with('\n') {
+                    response.push('\n');
+                }
+                state.responses.extend(response.bytes());
+            }
+            state.queries.push(line);
+        }
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
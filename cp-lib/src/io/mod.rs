@@ -1 +1,5 @@
+pub mod fast_scanner;
+pub mod input;
+pub mod interactive;
 pub mod scanner;
+pub mod writer;
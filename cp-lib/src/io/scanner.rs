@@ -1,16 +1,85 @@
-use std::io::{self, BufRead, Cursor};
+use std::fmt;
+use std::io::{self, BufRead, Cursor, Read};
+use std::marker::PhantomData;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScanErrorKind {
+    /// The input ended before the token.
+    UnexpectedEof,
+    /// In line mode, the current line has no more tokens.
+    EndOfLine,
+    /// The token does not parse as the requested type.
+    InvalidToken,
+    /// Reading from the underlying reader failed.
+    Io(String),
+}
+
+/// Why a token could not be read, and where.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScanError {
+    pub kind: ScanErrorKind,
+    /// The offending token for `InvalidToken`.
+    pub token: Option<String>,
+    /// The type that was requested.
+    pub type_name: &'static str,
+    /// 1-based index of the token in the input.
+    pub position: usize,
+    /// 1-based line of the token; at end of input, the number of lines read.
+    pub line: usize,
+}
+
+impl fmt::Display for ScanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.kind {
+            ScanErrorKind::UnexpectedEof => write!(
+                f,
+                "Unexpected end of input reading token {} as type {} (after line {})",
+                self.position, self.type_name, self.line
+            ),
+            ScanErrorKind::EndOfLine => write!(
+                f,
+                "Unexpected end of line reading token {} as type {} (line {})",
+                self.position, self.type_name, self.line
+            ),
+            ScanErrorKind::InvalidToken => write!(
+                f,
+                "Failed to parse '{}' as type {} at token {} (line {})",
+                self.token.as_deref().unwrap_or_default(),
+                self.type_name,
+                self.position,
+                self.line
+            ),
+            ScanErrorKind::Io(message) => write!(f, "Error reading input: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for ScanError {}
 
 pub struct Scanner<R: BufRead> {
     buffer: Vec<String>,
     reader: R,
+    /// Tokens taken so far, including ones that failed to parse.
+    tokens: usize,
+    /// Lines read so far; the tokens in `buffer` are from the last one.
+    lines: usize,
+    /// The last line read, without its line break.
+    current_line: String,
+    /// Whether `current_line` has not been finished by `next_line`.
+    in_line: bool,
+    /// Whether tokens stop at the end of the current line.
+    line_mode: bool,
 }
 
 impl Scanner<io::StdinLock<'static>> {
     pub fn new() -> Self {
-        Self {
-            buffer: Vec::new(),
-            reader: Box::leak(Box::new(io::stdin())).lock(), // I don't like this but it works :/
-        }
+        Self::from_reader(io::stdin().lock())
+    }
+}
+
+impl Default for Scanner<io::StdinLock<'static>> {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -19,23 +88,235 @@ impl<R: BufRead> Scanner<R> {
         Self {
             buffer: Vec::new(),
             reader,
+            tokens: 0,
+            lines: 0,
+            current_line: String::new(),
+            in_line: false,
+            line_mode: false,
         }
     }
 
-    pub fn next<T: std::str::FromStr>(&mut self) -> T {
-        loop {
-            if let Some(token) = self.buffer.pop() {
-                return token.parse().ok().unwrap();
+    /// In line mode, tokens are only taken from the current line: at its end
+    /// `has_next` is false and `scan` fails with `EndOfLine` until
+    /// `next_line` or `tokens_in_line` finishes the line. As with `getline`,
+    /// the line a token was just read from is the current line, so after
+    /// `let n: usize = sc.next()` the first `next_line` returns the rest of
+    /// that line, usually `""`. Blank lines are lines without tokens rather
+    /// than being skipped.
+    pub fn set_line_mode(&mut self, enabled: bool) {
+        self.line_mode = enabled;
+    }
+
+    /// An error about the token being read.
+    fn error<T>(&self, kind: ScanErrorKind, token: Option<String>) -> ScanError {
+        ScanError {
+            kind,
+            token,
+            type_name: std::any::type_name::<T>(),
+            position: self.tokens + 1,
+            line: self.lines,
+        }
+    }
+
+    /// An error about the token just taken, which parsed but is not valid
+    /// as `type_name`.
+    fn rejected(&self, token: String, type_name: &'static str) -> ScanError {
+        ScanError {
+            kind: ScanErrorKind::InvalidToken,
+            token: Some(token),
+            type_name,
+            position: self.tokens,
+            line: self.lines,
+        }
+    }
+
+    /// Reads the next line into `current_line` and its tokens into `buffer`.
+    /// Returns false at end of input.
+    fn read_line<T>(&mut self) -> Result<bool, ScanError> {
+        let mut line = String::new();
+        match self.reader.read_line(&mut line) {
+            Ok(0) => Ok(false),
+            Ok(_) => {
+                self.lines += 1;
+                let len = line.trim_end_matches(['\n', '\r']).len();
+                line.truncate(len);
+                self.buffer = line.split_whitespace().rev().map(String::from).collect();
+                self.current_line = line;
+                self.in_line = true;
+                Ok(true)
             }
-            let mut line = String::new();
-            match self.reader.read_line(&mut line) {
-                Ok(0) => panic!("Unexpected end of input"),
-                Ok(_) => {
-                    self.buffer = line.split_whitespace().rev().map(String::from).collect();
-                }
-                Err(e) => panic!("Error reading input: {}", e),
+            Err(e) => Err(self.error::<T>(ScanErrorKind::Io(e.to_string()), None)),
+        }
+    }
+
+    /// Reads lines until one has a token, or in line mode until the current
+    /// line ends. Returns false if there is no token.
+    fn fill_buffer<T>(&mut self) -> Result<bool, ScanError> {
+        while self.buffer.is_empty() {
+            if self.line_mode && self.in_line {
+                return Ok(false);
+            }
+            if !self.read_line::<T>()? {
+                return Ok(false);
             }
         }
+        Ok(true)
+    }
+
+    /// The unread part of the current line, from its next token on.
+    fn rest_of_line(&self) -> String {
+        if self.buffer.is_empty() {
+            return String::new();
+        }
+        let taken = self.current_line.split_whitespace().count() - self.buffer.len();
+        let mut rest = self.current_line.trim_start();
+        for _ in 0..taken {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            rest = rest[end..].trim_start();
+        }
+        rest.to_string()
+    }
+
+    /// Whether another token follows. Reads ahead as far as the next token,
+    /// so it blocks on interactive input like `next` does; read errors count
+    /// as the end of input.
+    pub fn has_next(&mut self) -> bool {
+        self.fill_buffer::<()>().unwrap_or(false)
+    }
+
+    /// Reads the next token as `T`, reporting what went wrong instead of panicking.
+    pub fn scan<T: std::str::FromStr>(&mut self) -> Result<T, ScanError> {
+        if !self.fill_buffer::<T>()? {
+            let kind = if self.line_mode && self.in_line {
+                ScanErrorKind::EndOfLine
+            } else {
+                ScanErrorKind::UnexpectedEof
+            };
+            return Err(self.error::<T>(kind, None));
+        }
+        let token = self.buffer.pop().expect("buffer was filled");
+        let result = token
+            .parse()
+            .map_err(|_| self.error::<T>(ScanErrorKind::InvalidToken, Some(token)));
+        self.tokens += 1;
+        result
+    }
+
+    /// The next token as `T`, or `None` at end of input. A token that does
+    /// not parse is consumed and also gives `None`; use `scan` to tell apart.
+    pub fn try_next<T: std::str::FromStr>(&mut self) -> Option<T> {
+        self.scan().ok()
+    }
+
+    /// Reads a `Readable` value: a tuple, an array, a `Usize1` or any type
+    /// implementing the trait.
+    pub fn try_read<T: Readable>(&mut self) -> Result<T::Output, ScanError> {
+        T::read(self)
+    }
+
+    pub fn read<T: Readable>(&mut self) -> T::Output {
+        self.try_read::<T>().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Reads `n` values of a `Readable` type whose output differs from it,
+    /// e.g. `read_vec::<(Usize1, Usize1)>(m)` for 1-based edges.
+    pub fn read_vec<T: Readable>(&mut self, n: usize) -> Vec<T::Output> {
+        (0..n).map(|_| self.read::<T>()).collect()
+    }
+
+    pub fn try_dump<T: std::str::FromStr>(&mut self, n: usize) -> Result<Vec<T>, ScanError> {
+        (0..n).map(|_| self.scan()).collect()
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn next<T: std::str::FromStr>(&mut self) -> T {
+        self.scan().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// The next token as bytes.
+    pub fn bytes(&mut self) -> Vec<u8> {
+        self.next::<String>().into_bytes()
+    }
+
+    pub fn chars(&mut self) -> Vec<char> {
+        self.next::<String>().chars().collect()
+    }
+
+    /// `n` rows of a grid such as `#.#..`, one token per row.
+    pub fn grid(&mut self, n: usize) -> Vec<Vec<u8>> {
+        (0..n).map(|_| self.bytes()).collect()
+    }
+
+    pub fn char_grid(&mut self, n: usize) -> Vec<Vec<char>> {
+        (0..n).map(|_| self.chars()).collect()
+    }
+
+    /// A binary string such as `0110`, as `[false, true, true, false]`.
+    pub fn bits(&mut self) -> Vec<bool> {
+        let token: String = self.next();
+        if !token.bytes().all(|b| b == b'0' || b == b'1') {
+            panic!("{}", self.rejected(token, "binary string"));
+        }
+        token.bytes().map(|b| b == b'1').collect()
+    }
+
+    /// A string of decimal digits such as `2024`, as `[2, 0, 2, 4]`.
+    pub fn digits(&mut self) -> Vec<u8> {
+        let token: String = self.next();
+        if !token.bytes().all(|b| b.is_ascii_digit()) {
+            panic!("{}", self.rejected(token, "digit string"));
+        }
+        token.bytes().map(|b| b - b'0').collect()
+    }
+
+    /// The next line as is, spaces included and the line break removed; it
+    /// may be empty. If a line is partly read, its unread rest is returned
+    /// instead. `None` at end of input.
+    pub fn next_line(&mut self) -> Option<String> {
+        let line = if !self.buffer.is_empty() || (self.line_mode && self.in_line) {
+            self.rest_of_line()
+        } else {
+            match self.read_line::<String>() {
+                Ok(true) => std::mem::take(&mut self.current_line),
+                Ok(false) => return None,
+                Err(e) => panic!("{}", e),
+            }
+        };
+        self.tokens += self.buffer.len();
+        self.buffer.clear();
+        self.in_line = false;
+        Some(line)
+    }
+
+    /// Like `next_line`, but panics at end of input.
+    pub fn line(&mut self) -> String {
+        self.next_line()
+            .unwrap_or_else(|| panic!("{}", self.error::<String>(ScanErrorKind::UnexpectedEof, None)))
+    }
+
+    /// The tokens of `next_line`, e.g. all numbers up to the end of the line.
+    pub fn tokens_in_line<T: std::str::FromStr>(&mut self) -> Vec<T> {
+        let line = self.line();
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let first = self.tokens - tokens.len();
+        tokens
+            .iter()
+            .enumerate()
+            .map(|(i, token)| {
+                token.parse().unwrap_or_else(|_| {
+                    panic!(
+                        "{}",
+                        ScanError {
+                            kind: ScanErrorKind::InvalidToken,
+                            token: Some(token.to_string()),
+                            type_name: std::any::type_name::<T>(),
+                            position: first + i + 1,
+                            line: self.lines,
+                        }
+                    )
+                })
+            })
+            .collect()
     }
 
     pub fn dump<T: std::str::FromStr + std::fmt::Debug>(&mut self, n: usize) -> Vec<T>
@@ -44,35 +325,30 @@ impl<R: BufRead> Scanner<R> {
     {
         let mut result = Vec::with_capacity(n);
         for i in 0..n {
-            // Get the next token as a string first for better error handling
-            let token = loop {
-                if let Some(token) = self.buffer.pop() {
-                    break token;
-                }
-                let mut line = String::new();
-                match self.reader.read_line(&mut line) {
-                    Ok(0) => panic!(
-                        "Unexpected end of input while reading entry {} of {} for dump operation", 
-                        i + 1, n
+            match self.scan::<T>() {
+                Ok(value) => result.push(value),
+                Err(e) => match e.kind {
+                    ScanErrorKind::UnexpectedEof => panic!(
+                        "Unexpected end of input while reading entry {} of {} for dump operation",
+                        i + 1,
+                        n
+                    ),
+                    ScanErrorKind::EndOfLine => panic!(
+                        "Unexpected end of line while reading entry {} of {} for dump operation",
+                        i + 1,
+                        n
                     ),
-                    Ok(_) => {
-                        self.buffer = line.split_whitespace().rev().map(String::from).collect();
+                    ScanErrorKind::InvalidToken => {
+                        let token = e.token.unwrap_or_default();
+                        // Parse again for the parser's own error message.
+                        let reason = token.parse::<T>().unwrap_err();
+                        panic!(
+                            "Failed to parse '{}' as type {} at position {} of {} (line {}): {:?}",
+                            token, e.type_name, i + 1, n, e.line, reason
+                        )
                     }
-                    Err(e) => panic!("Error reading input: {}", e),
-                }
-            };
-
-            // Try to parse the token
-            match token.parse::<T>() {
-                Ok(value) => result.push(value),
-                Err(e) => panic!(
-                    "Failed to parse '{}' as type {} at position {} of {}: {:?}",
-                    token,
-                    std::any::type_name::<T>(),
-                    i + 1,
-                    n,
-                    e
-                ),
+                    ScanErrorKind::Io(_) => panic!("{}", e),
+                },
             }
         }
         result
@@ -83,4 +359,167 @@ impl Scanner<Cursor<Vec<u8>>> {
     pub fn from_string(input: String) -> Self {
         Scanner::from_reader(Cursor::new(input.into_bytes()))
     }
+
+    /// Reads all of stdin up front instead of line by line. Faster on large
+    /// inputs, but not for interactive problems: it waits for end of input.
+    pub fn read_all() -> Self {
+        Self::read_all_from(io::stdin().lock())
+    }
+
+    pub fn read_all_from(mut reader: impl Read) -> Self {
+        let mut input = Vec::new();
+        if let Err(e) = reader.read_to_end(&mut input) {
+            panic!("Error reading input: {}", e);
+        }
+        Scanner::from_reader(Cursor::new(input))
+    }
+}
+
+/// A value read from a `Scanner`, possibly spanning several tokens.
+///
+/// `Output` is what reading produces, so marker types such as `Usize1` can
+/// read as something else. Implement it for your own types, or declare
+/// structs with `readable!`.
+pub trait Readable {
+    type Output;
+    fn read<R: BufRead>(scanner: &mut Scanner<R>) -> Result<Self::Output, ScanError>;
+}
+
+macro_rules! impl_readable_from_str {
+    ($($t:ty),*) => {$(
+        impl Readable for $t {
+            type Output = $t;
+            fn read<R: BufRead>(scanner: &mut Scanner<R>) -> Result<$t, ScanError> {
+                scanner.scan()
+            }
+        }
+    )*};
+}
+
+impl_readable_from_str!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+impl_readable_from_str!(f32, f64, bool, char, String);
+
+macro_rules! impl_readable_tuple {
+    ($($t:ident),+) => {
+        impl<$($t: Readable),+> Readable for ($($t,)+) {
+            type Output = ($($t::Output,)+);
+            fn read<R: BufRead>(scanner: &mut Scanner<R>) -> Result<Self::Output, ScanError> {
+                Ok(($($t::read(scanner)?,)+))
+            }
+        }
+    };
+}
+
+impl_readable_tuple!(A);
+impl_readable_tuple!(A, B);
+impl_readable_tuple!(A, B, C);
+impl_readable_tuple!(A, B, C, D);
+impl_readable_tuple!(A, B, C, D, E);
+impl_readable_tuple!(A, B, C, D, E, F);
+impl_readable_tuple!(A, B, C, D, E, F, G);
+impl_readable_tuple!(A, B, C, D, E, F, G, H);
+
+impl<T: Readable, const N: usize> Readable for [T; N] {
+    type Output = [T::Output; N];
+    fn read<R: BufRead>(scanner: &mut Scanner<R>) -> Result<Self::Output, ScanError> {
+        let values = (0..N).map(|_| T::read(scanner)).collect::<Result<Vec<_>, _>>()?;
+        Ok(values.try_into().unwrap_or_else(|_| unreachable!("read exactly N values")))
+    }
+}
+
+/// Reads any `FromStr` type, for types without a `Readable` impl:
+/// `sc.read_vec::<Parsed<Ipv4Addr>>(n)`.
+pub struct Parsed<T>(PhantomData<T>);
+
+impl<T: std::str::FromStr> Readable for Parsed<T> {
+    type Output = T;
+    fn read<R: BufRead>(scanner: &mut Scanner<R>) -> Result<T, ScanError> {
+        scanner.scan()
+    }
+}
+
+/// A 1-based index, read as 0-based `usize`.
+pub enum Usize1 {}
+
+/// A 1-based signed value, read as `isize` minus one.
+pub enum Isize1 {}
+
+impl Readable for Usize1 {
+    type Output = usize;
+    fn read<R: BufRead>(scanner: &mut Scanner<R>) -> Result<usize, ScanError> {
+        let value: usize = scanner.scan()?;
+        value.checked_sub(1).ok_or_else(|| scanner.rejected(value.to_string(), "Usize1"))
+    }
+}
+
+impl Readable for Isize1 {
+    type Output = isize;
+    fn read<R: BufRead>(scanner: &mut Scanner<R>) -> Result<isize, ScanError> {
+        let value: isize = scanner.scan()?;
+        value.checked_sub(1).ok_or_else(|| scanner.rejected(value.to_string(), "Isize1"))
+    }
+}
+
+/// A token read as its bytes.
+pub enum Bytes {}
+
+/// A token read as its chars.
+pub enum Chars {}
+
+impl Readable for Bytes {
+    type Output = Vec<u8>;
+    fn read<R: BufRead>(scanner: &mut Scanner<R>) -> Result<Vec<u8>, ScanError> {
+        scanner.scan::<String>().map(String::into_bytes)
+    }
+}
+
+impl Readable for Chars {
+    type Output = Vec<char>;
+    fn read<R: BufRead>(scanner: &mut Scanner<R>) -> Result<Vec<char>, ScanError> {
+        scanner.scan::<String>().map(|token| token.chars().collect())
+    }
+}
+
+/// Declares a struct read field by field, in order. Field types are
+/// `Readable` types and stored as their output, so a `Usize1` field holds a
+/// `usize`:
+///
+/// ```
+/// use cp_lib::io::scanner::{Scanner, Usize1};
+/// use cp_lib::readable;
+///
+/// readable! {
+///     #[derive(Debug)]
+///     struct Edge { from: Usize1, to: Usize1, weight: i64 }
+/// }
+///
+/// let mut sc = Scanner::from_string("2\n1 2 5\n2 3 -1\n".to_string());
+/// let m: usize = sc.next();
+/// let edges = sc.read_vec::<Edge>(m);
+/// assert_eq!((edges[1].from, edges[1].to, edges[1].weight), (1, 2, -1));
+/// ```
+#[macro_export]
+macro_rules! readable {
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident {
+            $($field_vis:vis $field:ident : $ty:ty),* $(,)?
+        }
+    ) => {
+        $(#[$attr])*
+        $vis struct $name {
+            $($field_vis $field: <$ty as $crate::io::scanner::Readable>::Output,)*
+        }
+
+        impl $crate::io::scanner::Readable for $name {
+            type Output = $name;
+            fn read<R: ::std::io::BufRead>(
+                scanner: &mut $crate::io::scanner::Scanner<R>,
+            ) -> ::std::result::Result<$name, $crate::io::scanner::ScanError> {
+                Ok($name {
+                    $($field: scanner.try_read::<$ty>()?,)*
+                })
+            }
+        }
+    };
 }
@@ -0,0 +1,216 @@
+use std::io::{self, Cursor, StdoutLock, Write};
+
+/// Output is handed to the underlying writer once this much is buffered.
+const BUFFER_SIZE: usize = 1 << 16;
+
+/// Values a `Writer` can print. Integers are formatted by hand, without
+/// going through `fmt`; other `Display` types can be written with `write!`,
+/// as `Writer` implements `io::Write`.
+pub trait Print {
+    fn print_to(&self, buf: &mut Vec<u8>);
+}
+
+macro_rules! impl_print_unsigned {
+    ($($t:ty),*) => {$(
+        impl Print for $t {
+            #[inline]
+            fn print_to(&self, buf: &mut Vec<u8>) {
+                let mut digits = [0u8; 40];
+                let mut start = digits.len();
+                let mut value = *self;
+                loop {
+                    start -= 1;
+                    digits[start] = b'0' + (value % 10) as u8;
+                    value /= 10;
+                    if value == 0 {
+                        break;
+                    }
+                }
+                buf.extend_from_slice(&digits[start..]);
+            }
+        }
+    )*};
+}
+
+macro_rules! impl_print_signed {
+    ($($t:ty),*) => {$(
+        impl Print for $t {
+            #[inline]
+            fn print_to(&self, buf: &mut Vec<u8>) {
+                if *self < 0 {
+                    buf.push(b'-');
+                }
+                self.unsigned_abs().print_to(buf);
+            }
+        }
+    )*};
+}
+
+impl_print_unsigned!(u8, u16, u32, u64, u128, usize);
+impl_print_signed!(i8, i16, i32, i64, i128, isize);
+
+macro_rules! impl_print_display {
+    ($($t:ty),*) => {$(
+        impl Print for $t {
+            fn print_to(&self, buf: &mut Vec<u8>) {
+                write!(buf, "{}", self).expect("writing to a Vec cannot fail");
+            }
+        }
+    )*};
+}
+
+impl_print_display!(f32, f64, bool);
+
+impl Print for char {
+    fn print_to(&self, buf: &mut Vec<u8>) {
+        let mut encoded = [0; 4];
+        buf.extend_from_slice(self.encode_utf8(&mut encoded).as_bytes());
+    }
+}
+
+impl Print for str {
+    fn print_to(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(self.as_bytes());
+    }
+}
+
+impl Print for String {
+    fn print_to(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(self.as_bytes());
+    }
+}
+
+impl<T: Print + ?Sized> Print for &T {
+    fn print_to(&self, buf: &mut Vec<u8>) {
+        (**self).print_to(buf);
+    }
+}
+
+/// Buffered output: collects everything printed and writes it out in large
+/// chunks, instead of locking and flushing stdout on every `println!`.
+///
+/// The buffer is flushed when the writer is dropped. Interactive problems
+/// need `flush` after each query.
+pub struct Writer<W: Write> {
+    writer: W,
+    buf: Vec<u8>,
+}
+
+impl Writer<StdoutLock<'static>> {
+    pub fn new() -> Self {
+        Self::from_writer(io::stdout().lock())
+    }
+}
+
+impl Default for Writer<StdoutLock<'static>> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<W: Write> Writer<W> {
+    pub fn from_writer(writer: W) -> Self {
+        Self {
+            writer,
+            buf: Vec::with_capacity(BUFFER_SIZE),
+        }
+    }
+
+    fn write_buffer(&mut self) -> io::Result<()> {
+        self.writer.write_all(&self.buf)?;
+        self.buf.clear();
+        Ok(())
+    }
+
+    fn write_if_full(&mut self) {
+        if self.buf.len() >= BUFFER_SIZE {
+            self.write_buffer().unwrap_or_else(|e| panic!("Error writing output: {}", e));
+        }
+    }
+
+    pub fn print<T: Print>(&mut self, value: T) {
+        value.print_to(&mut self.buf);
+        self.write_if_full();
+    }
+
+    pub fn println<T: Print>(&mut self, value: T) {
+        value.print_to(&mut self.buf);
+        self.buf.push(b'\n');
+        self.write_if_full();
+    }
+
+    /// Prints the items separated by `sep`, without a line break.
+    pub fn join<T: Print>(&mut self, items: impl IntoIterator<Item = T>, sep: &str) {
+        for (i, item) in items.into_iter().enumerate() {
+            if i > 0 {
+                self.buf.extend_from_slice(sep.as_bytes());
+            }
+            item.print_to(&mut self.buf);
+            self.write_if_full();
+        }
+    }
+
+    /// Prints the items on one line, separated by spaces.
+    pub fn println_spaced<T: Print>(&mut self, items: impl IntoIterator<Item = T>) {
+        self.join(items, " ");
+        self.buf.push(b'\n');
+    }
+
+    /// Prints each item on its own line.
+    pub fn println_lines<T: Print>(&mut self, items: impl IntoIterator<Item = T>) {
+        for item in items {
+            self.println(item);
+        }
+    }
+
+    /// Prints `Yes` or `No`, as AtCoder expects.
+    pub fn yes_no(&mut self, condition: bool) {
+        self.println(if condition { "Yes" } else { "No" });
+    }
+
+    /// Prints `YES` or `NO`.
+    pub fn yes_no_upper(&mut self, condition: bool) {
+        self.println(if condition { "YES" } else { "NO" });
+    }
+
+    /// Writes out everything printed so far and flushes the underlying writer.
+    pub fn flush(&mut self) {
+        Write::flush(self).unwrap_or_else(|e| panic!("Error writing output: {}", e));
+    }
+}
+
+impl<W: Write> Write for Writer<W> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(data);
+        if self.buf.len() >= BUFFER_SIZE {
+            self.write_buffer()?;
+        }
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.write_buffer()?;
+        self.writer.flush()
+    }
+}
+
+impl<W: Write> Drop for Writer<W> {
+    fn drop(&mut self) {
+        // Errors cannot be reported from here; a closed stdout is not worth a
+        // panic during unwinding.
+        let _ = Write::flush(self);
+    }
+}
+
+impl Writer<Cursor<Vec<u8>>> {
+    /// A writer collecting its output in memory, for tests.
+    pub fn in_memory() -> Self {
+        Self::from_writer(Cursor::new(Vec::new()))
+    }
+
+    /// Everything printed so far.
+    pub fn contents(&mut self) -> String {
+        self.flush();
+        String::from_utf8_lossy(self.writer.get_ref()).into_owned()
+    }
+}
@@ -6,3 +6,5 @@
 
 pub mod io;
 pub mod algorithms;
+pub mod debug;
+pub mod math;
@@ -0,0 +1,314 @@
+use crate::algorithms::exponential::mod_inverse;
+use crate::io::scanner::{Readable, ScanError, Scanner};
+use crate::io::writer::Print;
+use std::cell::Cell;
+use std::fmt;
+use std::io::BufRead;
+use std::iter::{Product, Sum};
+use std::num::ParseIntError;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+/// An integer modulo the compile-time modulus `M`, kept in `[0, M)`.
+///
+/// ```
+/// use cp_lib::math::modint::Mint998;
+///
+/// let a = Mint998::new(-1);
+/// assert_eq!(a.value(), 998_244_352);
+/// assert_eq!((a + 2).value(), 1);
+/// assert_eq!(Mint998::new(2).inv() * 2, Mint998::new(1));
+/// ```
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct ModInt<const M: u32> {
+    value: u32,
+}
+
+pub type Mint998 = ModInt<998_244_353>;
+pub type Mint107 = ModInt<1_000_000_007>;
+
+impl<const M: u32> ModInt<M> {
+    /// `value mod M`; negative values wrap around.
+    pub fn new(value: i64) -> Self {
+        Self::raw(value.rem_euclid(M as i64) as u32)
+    }
+
+    /// Wraps a value already in `[0, M)` without reducing it.
+    #[inline]
+    pub fn raw(value: u32) -> Self {
+        debug_assert!(value < M);
+        Self { value }
+    }
+
+    #[inline]
+    pub fn modulus() -> u32 {
+        M
+    }
+
+    #[inline]
+    pub fn value(self) -> u32 {
+        self.value
+    }
+
+    #[inline]
+    fn mul_values(a: u32, b: u32) -> u32 {
+        (a as u64 * b as u64 % M as u64) as u32
+    }
+}
+
+thread_local! {
+    static BARRETT: Cell<Barrett> = const { Cell::new(Barrett::new(998_244_353)) };
+}
+
+/// Multiplication modulo a runtime `m` without a division: `im` is
+/// `ceil(2^64 / m)`, so `a * b * im >> 64` is the quotient or one more.
+/// Exact for `m < 2^31`.
+#[derive(Clone, Copy)]
+pub struct Barrett {
+    m: u32,
+    im: u64,
+}
+
+impl Barrett {
+    pub const fn new(m: u32) -> Self {
+        assert!(m >= 1 && m < 1 << 31, "Barrett reduction needs 1 <= m < 2^31");
+        Self {
+            m,
+            im: (u64::MAX / m as u64).wrapping_add(1),
+        }
+    }
+
+    #[inline]
+    pub fn modulus(&self) -> u32 {
+        self.m
+    }
+
+    /// `a * b mod m` for `a, b < m`.
+    #[inline]
+    pub fn mul(&self, a: u32, b: u32) -> u32 {
+        let z = a as u64 * b as u64;
+        let x = ((z as u128 * self.im as u128) >> 64) as u64;
+        let v = z.wrapping_sub(x.wrapping_mul(self.m as u64)) as u32;
+        if self.m <= v { v.wrapping_add(self.m) } else { v }
+    }
+}
+
+/// An integer modulo a modulus chosen at runtime, for problems that read it
+/// from the input. The modulus is shared by all values on the thread: set it
+/// with `DynModInt::set_modulus` before creating any (it starts as 998244353).
+/// Moduli must be below 2^31.
+/// Products use Barrett reduction instead of `%`.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct DynModInt {
+    value: u32,
+}
+
+impl DynModInt {
+    /// Sets the modulus for this thread. Values created under another
+    /// modulus must not be used afterwards.
+    pub fn set_modulus(m: u32) {
+        BARRETT.with(|barrett| barrett.set(Barrett::new(m)));
+    }
+
+    /// `value mod m`; negative values wrap around.
+    pub fn new(value: i64) -> Self {
+        Self::raw(value.rem_euclid(Self::modulus() as i64) as u32)
+    }
+
+    /// Wraps a value already in `[0, m)` without reducing it.
+    #[inline]
+    pub fn raw(value: u32) -> Self {
+        debug_assert!(value < Self::modulus());
+        Self { value }
+    }
+
+    #[inline]
+    pub fn modulus() -> u32 {
+        BARRETT.with(|barrett| barrett.get().modulus())
+    }
+
+    #[inline]
+    pub fn value(self) -> u32 {
+        self.value
+    }
+
+    #[inline]
+    fn mul_values(a: u32, b: u32) -> u32 {
+        BARRETT.with(|barrett| barrett.get().mul(a, b))
+    }
+}
+
+/// Operators and conversions shared by `ModInt` and `DynModInt`, written in
+/// terms of `raw`, `modulus` and `mul_values`. Operators take anything
+/// convertible, so `x + 1` and `x * 2` work with plain integers.
+macro_rules! impl_modint {
+    ([$($generics:tt)*] $t:ty) => {
+        impl<$($generics)*> $t {
+            /// `self^exp` by binary exponentiation.
+            pub fn pow(self, mut exp: u64) -> Self {
+                let mut base = self.value;
+                let mut res = 1 % Self::modulus();
+                while exp > 0 {
+                    if exp & 1 == 1 {
+                        res = Self::mul_values(res, base);
+                    }
+                    base = Self::mul_values(base, base);
+                    exp >>= 1;
+                }
+                Self::raw(res)
+            }
+
+            /// The multiplicative inverse, by the extended Euclidean algorithm,
+            /// so the modulus need not be prime.
+            ///
+            /// Panics if `self` is not coprime to the modulus.
+            pub fn inv(self) -> Self {
+                match mod_inverse(self.value, Self::modulus()) {
+                    Some(inv) => Self::raw(inv),
+                    None => panic!("{} has no inverse modulo {}", self.value, Self::modulus()),
+                }
+            }
+        }
+
+        impl<T: Into<$t>, $($generics)*> Add<T> for $t {
+            type Output = Self;
+            #[inline]
+            fn add(self, rhs: T) -> Self {
+                let sum = self.value as u64 + rhs.into().value as u64;
+                let m = Self::modulus() as u64;
+                Self::raw(if sum >= m { sum - m } else { sum } as u32)
+            }
+        }
+
+        impl<T: Into<$t>, $($generics)*> Sub<T> for $t {
+            type Output = Self;
+            #[inline]
+            fn sub(self, rhs: T) -> Self {
+                let rhs = rhs.into().value;
+                if self.value >= rhs {
+                    Self::raw(self.value - rhs)
+                } else {
+                    Self::raw((self.value as u64 + Self::modulus() as u64 - rhs as u64) as u32)
+                }
+            }
+        }
+
+        impl<T: Into<$t>, $($generics)*> Mul<T> for $t {
+            type Output = Self;
+            #[inline]
+            fn mul(self, rhs: T) -> Self {
+                Self::raw(Self::mul_values(self.value, rhs.into().value))
+            }
+        }
+
+        impl<T: Into<$t>, $($generics)*> Div<T> for $t {
+            type Output = Self;
+            #[allow(clippy::suspicious_arithmetic_impl)]
+            fn div(self, rhs: T) -> Self {
+                self * rhs.into().inv()
+            }
+        }
+
+        impl<$($generics)*> Neg for $t {
+            type Output = Self;
+            #[inline]
+            fn neg(self) -> Self {
+                Self::default() - self
+            }
+        }
+
+        impl_modint!(@assign [$($generics)*] $t; AddAssign add_assign Add add);
+        impl_modint!(@assign [$($generics)*] $t; SubAssign sub_assign Sub sub);
+        impl_modint!(@assign [$($generics)*] $t; MulAssign mul_assign Mul mul);
+        impl_modint!(@assign [$($generics)*] $t; DivAssign div_assign Div div);
+
+        impl<$($generics)*> Sum for $t {
+            fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+                iter.fold(Self::default(), |acc, x| acc + x)
+            }
+        }
+
+        impl<'a, $($generics)*> Sum<&'a $t> for $t {
+            fn sum<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+                iter.copied().sum()
+            }
+        }
+
+        impl<$($generics)*> Product for $t {
+            fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+                iter.fold(Self::from(1u32), |acc, x| acc * x)
+            }
+        }
+
+        impl<'a, $($generics)*> Product<&'a $t> for $t {
+            fn product<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+                iter.copied().product()
+            }
+        }
+
+        impl<$($generics)*> fmt::Display for $t {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                fmt::Display::fmt(&self.value, f)
+            }
+        }
+
+        impl<$($generics)*> Print for $t {
+            #[inline]
+            fn print_to(&self, buf: &mut Vec<u8>) {
+                self.value.print_to(buf);
+            }
+        }
+
+        impl<$($generics)*> fmt::Debug for $t {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                fmt::Debug::fmt(&self.value, f)
+            }
+        }
+
+        /// Parses any integer that fits in an `i128`, reduced modulo the modulus.
+        impl<$($generics)*> FromStr for $t {
+            type Err = ParseIntError;
+            fn from_str(s: &str) -> Result<Self, ParseIntError> {
+                s.parse::<i128>().map(Self::from)
+            }
+        }
+
+        impl<$($generics)*> Readable for $t {
+            type Output = Self;
+            fn read<R: BufRead>(scanner: &mut Scanner<R>) -> Result<Self, ScanError> {
+                scanner.scan()
+            }
+        }
+
+        impl_modint!(@from [$($generics)*] $t; u8 u16 u32 u64 u128 usize i8 i16 i32 i64 i128 isize);
+    };
+    (@assign [$($generics:tt)*] $t:ty; $assign:ident $assign_fn:ident $op:ident $op_fn:ident) => {
+        impl<T: Into<$t>, $($generics)*> $assign<T> for $t {
+            #[inline]
+            fn $assign_fn(&mut self, rhs: T) {
+                *self = $op::$op_fn(*self, rhs);
+            }
+        }
+    };
+    (@from $generics:tt $t:ty; $($int:ty)*) => {
+        $(impl_modint!(@from_int $generics $t; $int);)*
+    };
+    (@from_int [$($generics:tt)*] $t:ty; $int:ty) => {
+        impl<$($generics)*> From<$int> for $t {
+            #[allow(irrefutable_let_patterns, clippy::unnecessary_cast)]
+            fn from(value: $int) -> Self {
+                // Signed values are reduced with `rem_euclid`; everything fits in i128
+                // apart from large u128s, which are reduced unsigned.
+                let m = Self::modulus();
+                if let Ok(value) = i128::try_from(value) {
+                    Self::raw(value.rem_euclid(m as i128) as u32)
+                } else {
+                    Self::raw((value as u128 % m as u128) as u32)
+                }
+            }
+        }
+    };
+}
+
+impl_modint!([const M: u32] ModInt<M>);
+impl_modint!([] DynModInt);
@@ -0,0 +1,108 @@
+#[cfg(test)]
+mod tests {
+    use cp_lib::algorithms::exponential::{binpow, mod_inverse, mod_inverse_prime, mul_mod, pow_mod};
+
+    const MOD: u64 = 1_000_000_007;
+
+    fn naive_pow_mod(base: u64, exp: u64, m: u64) -> u64 {
+        let mut res = 1 % m;
+        for _ in 0..exp {
+            res = res * (base % m) % m;
+        }
+        res
+    }
+
+    #[test]
+    fn test_binpow() {
+        assert_eq!(binpow(2i64, 10u32), 1024);
+        assert_eq!(binpow(-3i64, 3u32), -27);
+        assert_eq!(binpow(7u64, 0u32), 1);
+    }
+
+    #[test]
+    fn test_pow_mod_matches_naive() {
+        for m in [1u64, 2, 7, 1000, MOD] {
+            for base in [0u64, 1, 2, 10, 999_999, MOD + 5] {
+                for exp in 0u64..50 {
+                    assert_eq!(pow_mod(base, exp, m), naive_pow_mod(base, exp, m), "{}^{} mod {}", base, exp, m);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_pow_mod_edge_cases() {
+        assert_eq!(pow_mod(5u64, 0u32, MOD), 1);
+        assert_eq!(pow_mod(0u64, 0u32, MOD), 1);
+        assert_eq!(pow_mod(5u64, 0u32, 1), 0);
+        assert_eq!(pow_mod(5u64, 123u32, 1), 0);
+        assert_eq!(pow_mod(0u64, 5u32, MOD), 0);
+    }
+
+    #[test]
+    fn test_pow_mod_large_exponents() {
+        // Fermat: a^(p-1) = 1 (mod p).
+        assert_eq!(pow_mod(2u64, MOD - 1, MOD), 1);
+        assert_eq!(pow_mod(3u64, u64::MAX, 2), 1);
+        assert_eq!(pow_mod(2u64, u128::MAX, 1 << 40), 0);
+        // Exponents reduce modulo p - 1.
+        let reduced = (u64::MAX as u128 % (MOD as u128 - 1)) as u64;
+        assert_eq!(pow_mod(2u64, u64::MAX, MOD), pow_mod(2u64, reduced, MOD));
+    }
+
+    #[test]
+    fn test_pow_mod_signed_base() {
+        assert_eq!(pow_mod(-2i64, 3u32, 1_000_000_007), 1_000_000_007 - 8);
+        assert_eq!(pow_mod(-2i64, 2u32, 1_000_000_007), 4);
+        assert_eq!(pow_mod(-1i32, 5u32, 7), 6);
+    }
+
+    #[test]
+    fn test_mul_mod_64_bit() {
+        let m = (1u64 << 61) - 1;
+        let a = m - 1;
+        assert_eq!(mul_mod(a, a, m), 1);
+        assert_eq!(mul_mod(u64::MAX, u64::MAX, u64::MAX), 0);
+        assert_eq!(mul_mod(u64::MAX - 1, u64::MAX - 1, u64::MAX), 1);
+        assert_eq!(mul_mod(-3i64, 5, 7), 6);
+        assert_eq!(mul_mod(123u64, 456, 1), 0);
+    }
+
+    #[test]
+    fn test_pow_mod_64_bit_modulus() {
+        let m = (1u64 << 61) - 1;
+        assert_eq!(pow_mod(3u64, m - 1, m), 1);
+        assert_eq!(pow_mod(37u64, m, m), 37);
+    }
+
+    #[test]
+    fn test_mod_inverse_prime() {
+        for a in 1u64..200 {
+            let inv = mod_inverse_prime(a, MOD);
+            assert_eq!(a * inv % MOD, 1);
+        }
+        assert_eq!(mod_inverse_prime(2i64, 998_244_353), 499_122_177);
+        assert_eq!(mod_inverse_prime(3u64, 1), 0);
+    }
+
+    #[test]
+    fn test_mod_inverse_extended_euclid() {
+        for m in [2u64, 9, 10, 12, 1000, MOD, (1 << 61) - 1] {
+            for a in 0u64..100 {
+                let expected = (1..m.min(2000)).find(|&x| mul_mod(a, x, m) == 1 % m);
+                match mod_inverse(a, m) {
+                    Some(inv) => {
+                        assert!(inv < m);
+                        assert_eq!(mul_mod(a, inv, m), 1 % m, "{}^-1 mod {}", a, m);
+                    }
+                    None => assert_eq!(expected, None, "{} mod {} should be invertible", a, m),
+                }
+            }
+        }
+        assert_eq!(mod_inverse(3u32, 10), Some(7));
+        assert_eq!(mod_inverse(4u32, 10), None);
+        assert_eq!(mod_inverse(-3i64, 10), Some(3));
+        assert_eq!(mod_inverse(5u64, 1), Some(0));
+        assert_eq!(mod_inverse(MOD - 1, MOD), Some(MOD - 1));
+    }
+}
@@ -0,0 +1,46 @@
+#[cfg(test)]
+#[cfg(any(feature = "local", debug_assertions))]
+mod tests {
+    use cp_lib::debug;
+    use cp_lib::debug::{format_entries, table};
+
+    #[test]
+    fn test_table_aligns_columns() {
+        let grid = vec![vec![1, 200, 3], vec![-40, 5, 6]];
+        assert_eq!(table(&grid), "  1 200 3\n-40   5 6");
+
+        let chars = vec![vec!['#', '.'], vec!['.', '#']];
+        assert_eq!(table(&chars), "# .\n. #");
+    }
+
+    #[test]
+    fn test_wrap_picks_table_for_2d_vectors() {
+        let grid = vec![vec![1, 2], vec![3, 4]];
+        let row = vec![1, 2];
+        let grid_ref = &grid;
+
+        assert_eq!(cp_lib::__debug_value!(grid), "1 2\n3 4");
+        assert_eq!(cp_lib::__debug_value!(grid_ref), "1 2\n3 4");
+        assert_eq!(cp_lib::__debug_value!(row), "[1, 2]");
+        assert_eq!(cp_lib::__debug_value!("s"), "\"s\"");
+    }
+
+    #[test]
+    fn test_format_entries() {
+        let line = format_entries("src/main.rs", 7, &[("n", "3".to_string()), ("a", "[1, 2]".to_string())]);
+        assert_eq!(line, "[src/main.rs:7] n = 3, a = [1, 2]");
+
+        let block = format_entries("src/main.rs", 9, &[("n", "2".to_string()), ("grid", "1 2\n3 4".to_string())]);
+        assert_eq!(block, "[src/main.rs:9]\nn = 2\ngrid =\n  1 2\n  3 4");
+    }
+
+    #[test]
+    fn test_debug_macro_accepts_values_and_formats() {
+        let n = 5;
+        let grid = vec![vec!['#', '.'], vec!['.', '#']];
+        let mask = 0b1011u32;
+        debug!(n, grid, mask => bin, n * 2,);
+        debug!();
+        assert_eq!(cp_lib::__debug_value!(mask, bin), "0b1011");
+    }
+}
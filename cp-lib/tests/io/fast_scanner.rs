@@ -0,0 +1,153 @@
+#[cfg(test)]
+mod tests {
+    use cp_lib::io::fast_scanner::FastScanner;
+    use cp_lib::io::scanner::Scanner;
+
+    /// Returns at most `chunk` bytes per `read`, so tokens straddle refills.
+    struct Trickle<'a> {
+        data: &'a [u8],
+        chunk: usize,
+    }
+
+    impl std::io::Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let n = self.chunk.min(buf.len()).min(self.data.len());
+            buf[..n].copy_from_slice(&self.data[..n]);
+            self.data = &self.data[n..];
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn test_fast_scanner_integer_types() {
+        let input = "127 -32768 2147483647 -9223372036854775808 18446744073709551615 1000 +7".to_string();
+        let mut scanner = FastScanner::from_string(input);
+
+        assert_eq!(scanner.next::<i8>(), 127);
+        assert_eq!(scanner.next::<i16>(), -32768);
+        assert_eq!(scanner.next::<i32>(), 2147483647);
+        assert_eq!(scanner.next::<i64>(), i64::MIN);
+        assert_eq!(scanner.next::<u64>(), u64::MAX);
+        assert_eq!(scanner.next::<usize>(), 1000);
+        assert_eq!(scanner.next::<i32>(), 7);
+    }
+
+    #[test]
+    fn test_fast_scanner_other_types() {
+        let input = "hello 0.125 -2.5e3 x true".to_string();
+        let mut scanner = FastScanner::from_string(input);
+
+        assert_eq!(scanner.next::<String>(), "hello");
+        assert_eq!(scanner.next::<f64>(), 0.125);
+        assert_eq!(scanner.next::<f32>(), -2500.0);
+        assert_eq!(scanner.next::<char>(), 'x');
+        assert!(scanner.next::<bool>());
+    }
+
+    #[test]
+    fn test_fast_scanner_from_str_fallback() {
+        let input = "127.0.0.1 ab".to_string();
+        let mut scanner = FastScanner::from_string(input);
+
+        let ip: std::net::Ipv4Addr = scanner.parse();
+        assert_eq!(ip, std::net::Ipv4Addr::LOCALHOST);
+        assert_eq!(scanner.next::<Vec<u8>>(), b"ab");
+    }
+
+    #[test]
+    fn test_fast_scanner_whitespace_and_dump() {
+        let input = "\n\n  3\r\n10\t20\n\n30  \n".to_string();
+        let mut scanner = FastScanner::from_string(input);
+
+        let n: usize = scanner.next();
+        let values: Vec<i64> = scanner.dump(n);
+        assert_eq!(values, vec![10, 20, 30]);
+        assert!(scanner.token().is_none());
+    }
+
+    #[test]
+    fn test_fast_scanner_tokens_across_refills() {
+        let long = "9".repeat(200_000);
+        let input = format!("12345 -678 {} 42\n", long);
+        for chunk in [1, 3, 7, 1 << 16] {
+            let reader = Trickle {
+                data: input.as_bytes(),
+                chunk,
+            };
+            let mut scanner = FastScanner::from_reader(reader);
+            assert_eq!(scanner.next::<u32>(), 12345);
+            assert_eq!(scanner.next::<i32>(), -678);
+            assert_eq!(scanner.next::<String>().len(), 200_000);
+            assert_eq!(scanner.next::<u8>(), 42);
+        }
+    }
+
+    #[test]
+    fn test_fast_scanner_matches_scanner() {
+        let mut state = 0x2545F4914F6CDD1Du64;
+        let mut input = String::from("5000\n");
+        for i in 0..5000 {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            let value = state as i64 >> (i % 60);
+            input.push_str(&value.to_string());
+            input.push(if i % 10 == 9 { '\n' } else { ' ' });
+        }
+
+        let mut fast = FastScanner::from_string(input.clone());
+        let mut scanner = Scanner::from_string(input);
+        let n: usize = fast.next();
+        assert_eq!(n, scanner.next::<usize>());
+        assert_eq!(fast.dump::<i64>(n), scanner.dump::<i64>(n));
+    }
+
+    #[test]
+    #[should_panic(expected = "Failed to parse 'abc' as type i32")]
+    fn test_fast_scanner_invalid_integer() {
+        let mut scanner = FastScanner::from_string("abc".to_string());
+        let _: i32 = scanner.next();
+    }
+
+    #[test]
+    #[should_panic(expected = "Failed to parse '-5' as type u32")]
+    fn test_fast_scanner_negative_unsigned() {
+        let mut scanner = FastScanner::from_string("-5".to_string());
+        let _: u32 = scanner.next();
+    }
+
+    #[test]
+    #[should_panic(expected = "Failed to parse '256' as type u8")]
+    fn test_fast_scanner_unsigned_overflow() {
+        let mut scanner = FastScanner::from_string("256".to_string());
+        let _: u8 = scanner.next();
+    }
+
+    #[test]
+    #[should_panic(expected = "Failed to parse '9223372036854775808' as type i64")]
+    fn test_fast_scanner_signed_overflow() {
+        let mut scanner = FastScanner::from_string("9223372036854775808".to_string());
+        let _: i64 = scanner.next();
+    }
+
+    #[test]
+    #[should_panic(expected = "Failed to parse '-9223372036854775809' as type i64")]
+    fn test_fast_scanner_signed_underflow() {
+        let mut scanner = FastScanner::from_string("-9223372036854775809".to_string());
+        let _: i64 = scanner.next();
+    }
+
+    #[test]
+    #[should_panic(expected = "Unexpected end of input while reading entry 4 of 5")]
+    fn test_fast_scanner_dump_insufficient_input() {
+        let mut scanner = FastScanner::from_string("1 2 3".to_string());
+        let _: Vec<i32> = scanner.dump(5);
+    }
+
+    #[test]
+    #[should_panic(expected = "Unexpected end of input")]
+    fn test_fast_scanner_empty_input() {
+        let mut scanner = FastScanner::from_string(String::new());
+        let _: i32 = scanner.next();
+    }
+}
@@ -0,0 +1,87 @@
+#[cfg(test)]
+mod tests {
+    use cp_lib::io::interactive::{MockInteractor, MockJudge};
+    use cp_lib::io::scanner::ScanErrorKind;
+
+    /// Judge for "guess the number in 1..=n": answers `? x` with `<`, `>` or
+    /// `=`, and accepts `! x`.
+    fn guessing_judge(n: u32, secret: u32) -> MockJudge {
+        MockJudge::new(&format!("{}\n", n), move |line| {
+            let (kind, value) = line.split_once(' ')?;
+            let value: u32 = value.parse().ok()?;
+            match kind {
+                "?" if secret < value => Some("<".to_string()),
+                "?" if secret > value => Some(">".to_string()),
+                "?" => Some("=".to_string()),
+                _ => None,
+            }
+        })
+    }
+
+    fn solve(io: &mut MockInteractor) {
+        let n: u32 = io.next();
+        let (mut lo, mut hi) = (1, n);
+        loop {
+            let mid = (lo + hi) / 2;
+            match io.ask::<char>(format_args!("? {}", mid)) {
+                '<' => hi = mid - 1,
+                '>' => lo = mid + 1,
+                _ => {
+                    io.say(format_args!("! {}", mid));
+                    return;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_interactor_binary_search_against_mock_judge() {
+        for secret in [1, 37, 99, 100] {
+            let judge = guessing_judge(100, secret);
+            solve(&mut judge.interactor());
+
+            let queries = judge.queries();
+            assert_eq!(queries.last(), Some(&format!("! {}", secret)));
+            assert!(queries.len() <= 8, "{} queries for {}", queries.len(), secret);
+        }
+    }
+
+    #[test]
+    fn test_interactor_reads_flush_pending_output() {
+        let judge = MockJudge::new("", |line| Some(format!("{} {}", line.len(), line.to_uppercase())));
+        let mut io = judge.interactor();
+
+        io.writer().print("ab");
+        io.writer().println("c");
+        let (len, upper) = io.read::<(usize, String)>();
+        assert_eq!((len, upper.as_str()), (3, "ABC"));
+        assert_eq!(io.ask::<(usize, String)>("x y").0, 3);
+        assert_eq!(judge.queries(), vec!["abc", "x y"]);
+    }
+
+    #[test]
+    fn test_mock_judge_reports_missing_flush() {
+        let judge = MockJudge::new("", |_| Some("1".to_string()));
+        let mut io = judge.interactor();
+
+        io.writer().println("? 1");
+        let error = io.scanner().scan::<i32>().unwrap_err();
+        assert_eq!(error.kind, ScanErrorKind::UnexpectedEof);
+        assert!(judge.queries().is_empty());
+
+        io.writer().flush();
+        assert_eq!(io.scanner().next::<i32>(), 1);
+    }
+
+    #[test]
+    fn test_mock_judge_does_not_read_ahead() {
+        let judge = MockJudge::new("2\n", |line| Some(format!("echo {}", line)));
+        let mut io = judge.interactor();
+
+        assert_eq!(io.next::<u32>(), 2);
+        assert!(!io.scanner().has_next());
+        assert_eq!(io.ask::<(String, String)>("first").1, "first");
+        assert_eq!(io.ask::<(String, String)>("second").1, "second");
+        assert!(!io.scanner().has_next());
+    }
+}
@@ -5,8 +5,10 @@
  */
 
 #[cfg(test)]
+#[allow(clippy::approx_constant)] // 3.14 and 2.718 are inputs, not approximations
 mod tests {
-    use cp_lib::io::scanner::Scanner;
+    use cp_lib::io::scanner::{Isize1, Parsed, ScanErrorKind, Scanner, Usize1};
+    use cp_lib::readable;
 
     #[test]
     fn test_scanner_single_integer() {
@@ -420,4 +422,383 @@ mod tests {
         assert_eq!(numbers, vec![10, 20, 30]);
         assert_eq!(greeting, "hello");
     }
-}
\ No newline at end of file
+
+    /// Hands out one line per `read` call and panics if asked for a line the
+    /// judge has not sent yet, like a pipe in an interactive problem would block.
+    struct InteractivePipe {
+        lines: Vec<&'static str>,
+        sent: std::rc::Rc<std::cell::Cell<usize>>,
+        read: usize,
+    }
+
+    impl std::io::Read for InteractivePipe {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            assert!(self.read < self.sent.get(), "scanner blocked waiting for unsent input");
+            let line = self.lines[self.read].as_bytes();
+            self.read += 1;
+            buf[..line.len()].copy_from_slice(line);
+            Ok(line.len())
+        }
+    }
+
+    #[test]
+    fn test_scanner_reads_interactive_input_line_by_line() {
+        let sent = std::rc::Rc::new(std::cell::Cell::new(1));
+        let pipe = InteractivePipe {
+            lines: vec!["100 3\n", "<\n", "=\n"],
+            sent: sent.clone(),
+            read: 0,
+        };
+        let mut scanner = Scanner::from_reader(std::io::BufReader::new(pipe));
+
+        // Both tokens of the first line come from a single read.
+        let n: u32 = scanner.next();
+        let k: u32 = scanner.next();
+        assert_eq!((n, k), (100, 3));
+
+        sent.set(2);
+        let first: String = scanner.next();
+        sent.set(3);
+        let second: String = scanner.next();
+        assert_eq!((first.as_str(), second.as_str()), ("<", "="));
+    }
+
+    /// Inputs of the tests above, read through every constructor.
+    const SUITE_INPUTS: [&str; 12] = [
+        "42",
+        "10 20 30 40",
+        "hello world",
+        "  10   20\t30\n40  ",
+        "Alice 25 3.14",
+        "\n\n42\n100 200\n\nhello\n",
+        "5\n1 2 3 4 5\nhello world",
+        "127 32767 2147483647 9223372036854775807 1000",
+        "3 3\n1 2 3\n4 5 6\n7 8 9",
+        "2\n3\n100 200 300\n4\n10 20 30 40",
+        "Alice 25\n3\n10 20 30\nhello",
+        "",
+    ];
+
+    fn read_tokens<R: std::io::BufRead>(mut scanner: Scanner<R>, count: usize) -> Vec<String> {
+        let tokens = (0..count).map(|_| scanner.next::<String>()).collect();
+        let past_end = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| scanner.next::<String>()));
+        assert!(past_end.is_err(), "expected end of input after {} tokens", count);
+        tokens
+    }
+
+    #[test]
+    fn test_scanner_constructors_read_identically() {
+        for input in SUITE_INPUTS {
+            let expected: Vec<String> = input.split_whitespace().map(String::from).collect();
+            let count = expected.len();
+
+            let buffered = Scanner::from_reader(std::io::BufReader::new(input.as_bytes()));
+            let whole = Scanner::read_all_from(input.as_bytes());
+            let string = Scanner::from_string(input.to_string());
+
+            assert_eq!(read_tokens(buffered, count), expected, "from_reader on {:?}", input);
+            assert_eq!(read_tokens(whole, count), expected, "read_all_from on {:?}", input);
+            assert_eq!(read_tokens(string, count), expected, "from_string on {:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_scanner_read_all_from_typed_reads() {
+        let mut scanner = Scanner::read_all_from("2\n3\n1 2 3\n2\n10 20".as_bytes());
+
+        let t: usize = scanner.next();
+        let sums: Vec<i64> = (0..t)
+            .map(|_| {
+                let n: usize = scanner.next();
+                scanner.dump::<i64>(n).iter().sum()
+            })
+            .collect();
+        assert_eq!(sums, vec![6, 30]);
+    }
+
+    #[test]
+    fn test_scanner_read_until_end_of_input() {
+        let input = "3 1\n4\n\n1 5  \n\n".to_string();
+        let mut scanner = Scanner::from_string(input);
+
+        let mut values = Vec::new();
+        while scanner.has_next() {
+            values.push(scanner.next::<i32>());
+        }
+        assert_eq!(values, vec![3, 1, 4, 1, 5]);
+        assert!(!scanner.has_next());
+        assert_eq!(scanner.try_next::<i32>(), None);
+    }
+
+    #[test]
+    fn test_scanner_try_next_loop() {
+        let mut scanner = Scanner::from_string("10 20\n30".to_string());
+
+        let mut sum = 0i64;
+        while let Some(x) = scanner.try_next::<i64>() {
+            sum += x;
+        }
+        assert_eq!(sum, 60);
+    }
+
+    #[test]
+    fn test_scanner_scan_reports_token_position_and_line() {
+        let mut scanner = Scanner::from_string("2\n7 abc\n".to_string());
+
+        assert_eq!(scanner.scan::<usize>(), Ok(2));
+        assert_eq!(scanner.scan::<i32>(), Ok(7));
+        let error = scanner.scan::<i32>().unwrap_err();
+        assert_eq!(error.kind, ScanErrorKind::InvalidToken);
+        assert_eq!(error.token.as_deref(), Some("abc"));
+        assert_eq!(error.type_name, "i32");
+        assert_eq!((error.position, error.line), (3, 2));
+        assert_eq!(error.to_string(), "Failed to parse 'abc' as type i32 at token 3 (line 2)");
+
+        let error = scanner.scan::<u8>().unwrap_err();
+        assert_eq!(error.kind, ScanErrorKind::UnexpectedEof);
+        assert_eq!((error.token, error.position, error.line), (None, 4, 2));
+    }
+
+    #[test]
+    fn test_scanner_try_dump() {
+        let mut scanner = Scanner::from_string("3\n1 2 3\n2\n4 x".to_string());
+
+        let n: usize = scanner.next();
+        assert_eq!(scanner.try_dump::<i64>(n), Ok(vec![1, 2, 3]));
+        let n: usize = scanner.next();
+        let error = scanner.try_dump::<i64>(n).unwrap_err();
+        assert_eq!((error.token.as_deref(), error.position, error.line), (Some("x"), 7, 4));
+
+        let error = scanner.try_dump::<i64>(1).unwrap_err();
+        assert_eq!(error.kind, ScanErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn test_scanner_try_next_skips_invalid_token() {
+        let mut scanner = Scanner::from_string("1 two 3".to_string());
+
+        assert_eq!(scanner.try_next::<u32>(), Some(1));
+        assert_eq!(scanner.try_next::<u32>(), None);
+        assert!(scanner.has_next());
+        assert_eq!(scanner.try_next::<u32>(), Some(3));
+    }
+
+    #[test]
+    #[should_panic(expected = "Failed to parse '-1' as type u64 at token 2 (line 1)")]
+    fn test_scanner_next_panics_with_token_and_type() {
+        let mut scanner = Scanner::from_string("5 -1".to_string());
+        let _: u64 = scanner.next();
+        let _: u64 = scanner.next();
+    }
+
+    #[test]
+    fn test_scanner_reads_tuples_and_arrays() {
+        let input = "3\n1 2 5\n2 3 -1\n3 1 7\nab 1 2.5 x true 9 -9 8\n4 5 6\n".to_string();
+        let mut scanner = Scanner::from_string(input);
+
+        let m: usize = scanner.next();
+        let edges = scanner.read_vec::<(usize, usize, i64)>(m);
+        assert_eq!(edges, vec![(1, 2, 5), (2, 3, -1), (3, 1, 7)]);
+
+        let wide = scanner.read::<(String, u8, f64, char, bool, u64, i32, i16)>();
+        assert_eq!(wide, ("ab".to_string(), 1, 2.5, 'x', true, 9, -9, 8));
+        assert_eq!(scanner.read::<[u32; 3]>(), [4, 5, 6]);
+    }
+
+    #[test]
+    fn test_scanner_one_based_adapters() {
+        let mut scanner = Scanner::from_string("2\n1 2\n2 3\n0 -4".to_string());
+
+        let m: usize = scanner.next();
+        let edges = scanner.read_vec::<(Usize1, Usize1)>(m);
+        assert_eq!(edges, vec![(0, 1), (1, 2)]);
+        assert_eq!(scanner.read::<Isize1>(), -1);
+        assert_eq!(scanner.read::<Isize1>(), -5);
+    }
+
+    #[test]
+    fn test_scanner_usize1_rejects_zero() {
+        let mut scanner = Scanner::from_string("3\n0".to_string());
+        let _: usize = scanner.next();
+
+        let error = scanner.try_read::<Usize1>().unwrap_err();
+        assert_eq!(error.kind, ScanErrorKind::InvalidToken);
+        assert_eq!((error.token.as_deref(), error.type_name, error.position, error.line), (Some("0"), "Usize1", 2, 2));
+    }
+
+    readable! {
+        #[derive(Debug, PartialEq)]
+        struct Query {
+            kind: u8,
+            range: (Usize1, usize),
+            value: i64,
+        }
+    }
+
+    #[test]
+    fn test_scanner_readable_struct() {
+        let mut scanner = Scanner::from_string("2\n1 1 3 10\n2 4 4 -7\n".to_string());
+
+        let q: usize = scanner.next();
+        let queries = scanner.read_vec::<Query>(q);
+        assert_eq!(
+            queries[1],
+            Query {
+                kind: 2,
+                range: (3, 4),
+                value: -7
+            }
+        );
+        assert_eq!(queries[0].range, (0, 3));
+    }
+
+    #[test]
+    fn test_scanner_parsed_adapter() {
+        let mut scanner = Scanner::from_string("127.0.0.1 10.0.0.1".to_string());
+        let addrs = scanner.read_vec::<Parsed<std::net::Ipv4Addr>>(2);
+        assert_eq!(addrs[0], std::net::Ipv4Addr::LOCALHOST);
+    }
+
+    #[test]
+    #[should_panic(expected = "Unexpected end of input reading token 4 as type i32")]
+    fn test_scanner_read_vec_tuples_insufficient_input() {
+        let mut scanner = Scanner::from_string("1 2 3".to_string());
+        let _ = scanner.read_vec::<(i32, i32)>(2);
+    }
+
+    #[test]
+    fn test_scanner_grids() {
+        let mut scanner = Scanner::from_string("3 4\n#.#.\n....\n.##.\n2\nab\ncd\n".to_string());
+
+        let (h, _w) = scanner.read::<(usize, usize)>();
+        let grid = scanner.grid(h);
+        assert_eq!(grid, vec![b"#.#.".to_vec(), b"....".to_vec(), b".##.".to_vec()]);
+        assert_eq!(grid[2][1], b'#');
+
+        let n: usize = scanner.next();
+        assert_eq!(scanner.char_grid(n), vec![vec!['a', 'b'], vec!['c', 'd']]);
+    }
+
+    #[test]
+    fn test_scanner_byte_strings() {
+        let mut scanner = Scanner::from_string("hello 0110 2024 xyz".to_string());
+
+        assert_eq!(scanner.bytes(), b"hello");
+        assert_eq!(scanner.bits(), vec![false, true, true, false]);
+        assert_eq!(scanner.digits(), vec![2, 0, 2, 4]);
+        assert_eq!(scanner.chars(), vec!['x', 'y', 'z']);
+    }
+
+    #[test]
+    #[should_panic(expected = "Failed to parse '0120' as type binary string at token 2 (line 1)")]
+    fn test_scanner_bits_rejects_other_digits() {
+        let mut scanner = Scanner::from_string("4 0120".to_string());
+        let _: usize = scanner.next();
+        let _ = scanner.bits();
+    }
+
+    #[test]
+    #[should_panic(expected = "Failed to parse '12a' as type digit string")]
+    fn test_scanner_digits_rejects_letters() {
+        let mut scanner = Scanner::from_string("12a".to_string());
+        let _ = scanner.digits();
+    }
+
+    #[test]
+    fn test_scanner_whole_lines() {
+        let input = "2\nhello  big world \r\n\n1 2 3\nlast".to_string();
+        let mut scanner = Scanner::from_string(input);
+
+        let n: usize = scanner.next();
+        assert_eq!(n, 2);
+        assert_eq!(scanner.line(), "hello  big world ");
+        assert_eq!(scanner.line(), "");
+        let _: i32 = scanner.next();
+        assert_eq!(scanner.line(), "2 3");
+        assert_eq!(scanner.line(), "last");
+        assert!(!scanner.has_next());
+    }
+
+    #[test]
+    #[should_panic(expected = "Unexpected end of input")]
+    fn test_scanner_line_at_end_of_input() {
+        let mut scanner = Scanner::from_string("1\n".to_string());
+        let _: i32 = scanner.next();
+        let _ = scanner.line();
+    }
+
+    #[test]
+    fn test_scanner_next_line_keeps_rest_of_partly_read_line() {
+        let mut scanner = Scanner::from_string("3  a   b c \n\n  indented\n".to_string());
+
+        let _: u32 = scanner.next();
+        let _: char = scanner.next();
+        assert_eq!(scanner.next_line().as_deref(), Some("b c "));
+        assert_eq!(scanner.next_line().as_deref(), Some(""));
+        assert_eq!(scanner.next_line().as_deref(), Some("  indented"));
+        assert_eq!(scanner.next_line(), None);
+    }
+
+    #[test]
+    fn test_scanner_tokens_in_line() {
+        let mut scanner = Scanner::from_string("2\n1 2 3\n\n4 5\n".to_string());
+
+        let q: usize = scanner.next();
+        let first = scanner.tokens_in_line::<i32>();
+        assert_eq!((q, first), (2, vec![1, 2, 3]));
+        assert_eq!(scanner.tokens_in_line::<i32>(), Vec::<i32>::new());
+        let _: i32 = scanner.next();
+        assert_eq!(scanner.tokens_in_line::<i32>(), vec![5]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Failed to parse 'x' as type i64 at token 4 (line 2)")]
+    fn test_scanner_tokens_in_line_reports_position() {
+        let mut scanner = Scanner::from_string("1\n2 3 x 4\n".to_string());
+        let _: i64 = scanner.next();
+        let _ = scanner.tokens_in_line::<i64>();
+    }
+
+    #[test]
+    fn test_scanner_line_mode_stops_at_line_end() {
+        let mut scanner = Scanner::from_string("\n\n5\n".to_string());
+        scanner.set_line_mode(true);
+        assert!(!scanner.has_next());
+        assert_eq!(scanner.next_line().as_deref(), Some(""));
+        assert!(!scanner.has_next());
+        assert_eq!(scanner.next_line().as_deref(), Some(""));
+        assert!(scanner.has_next());
+
+        let mut scanner = Scanner::from_string("1 2 3\n\n4 5\n".to_string());
+        scanner.set_line_mode(true);
+        let mut rows = Vec::new();
+        loop {
+            let mut row = Vec::new();
+            while scanner.has_next() {
+                row.push(scanner.next::<i32>());
+            }
+            if scanner.next_line().is_none() {
+                break;
+            }
+            rows.push(row);
+        }
+        assert_eq!(rows, vec![vec![1, 2, 3], vec![], vec![4, 5]]);
+    }
+
+    #[test]
+    fn test_scanner_line_mode_end_of_line_error() {
+        let mut scanner = Scanner::from_string("7\n8\n".to_string());
+        scanner.set_line_mode(true);
+
+        assert_eq!(scanner.next::<u8>(), 7);
+        let error = scanner.scan::<u8>().unwrap_err();
+        assert_eq!((error.kind, error.position, error.line), (ScanErrorKind::EndOfLine, 2, 1));
+
+        // The getline rule: the line of the last token is still current.
+        assert_eq!(scanner.next_line().as_deref(), Some(""));
+        assert_eq!(scanner.next::<u8>(), 8);
+
+        scanner.set_line_mode(false);
+        assert_eq!(scanner.next_line(), None);
+    }
+}
@@ -0,0 +1,108 @@
+#[cfg(test)]
+mod tests {
+    use cp_lib::io::writer::Writer;
+    use std::io::Write;
+
+    /// Records every `write` call reaching the underlying writer.
+    #[derive(Default)]
+    struct Recorder {
+        writes: Vec<usize>,
+        flushed: bool,
+    }
+
+    impl Write for &mut Recorder {
+        fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+            self.writes.push(data.len());
+            Ok(data.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            self.flushed = true;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_writer_integers() {
+        let mut out = Writer::in_memory();
+        out.println(0u8);
+        out.println(i8::MIN);
+        out.println(i64::MIN);
+        out.println(u64::MAX);
+        out.println(i128::MIN);
+        out.println(-42isize);
+        out.print(7usize);
+
+        let expected = format!("0\n-128\n{}\n{}\n{}\n-42\n7", i64::MIN, u64::MAX, i128::MIN);
+        assert_eq!(out.contents(), expected);
+    }
+
+    #[test]
+    fn test_writer_other_values() {
+        let mut out = Writer::in_memory();
+        out.print("ab");
+        out.print('é');
+        out.print(' ');
+        out.print(String::from("cd"));
+        out.print(' ');
+        out.print(2.5f64);
+        out.print(' ');
+        out.println(true);
+        writeln!(out, "{:>4}|{:.3}", 12, 1.0f64 / 3.0).unwrap();
+
+        assert_eq!(out.contents(), "abé cd 2.5 true\n  12|0.333\n");
+    }
+
+    #[test]
+    fn test_writer_slices_and_answers() {
+        let a = vec![3, -1, 4];
+        let words = ["x", "y"];
+        let mut out = Writer::in_memory();
+        out.println_spaced(&a);
+        out.println_spaced(Vec::<i32>::new());
+        out.println_lines(words);
+        out.join(a.iter().map(|x| x * 2), ",");
+        out.println("");
+        out.yes_no(true);
+        out.yes_no(false);
+        out.yes_no_upper(true);
+        out.yes_no_upper(false);
+
+        assert_eq!(out.contents(), "3 -1 4\n\nx\ny\n6,-2,8\nYes\nNo\nYES\nNO\n");
+    }
+
+    #[test]
+    fn test_writer_buffers_until_full_or_dropped() {
+        let mut recorder = Recorder::default();
+        {
+            let mut out = Writer::from_writer(&mut recorder);
+            for i in 0..10 {
+                out.println(i);
+            }
+        }
+        assert_eq!(recorder.writes, vec![20]);
+        assert!(recorder.flushed);
+
+        let mut recorder = Recorder::default();
+        {
+            let mut out = Writer::from_writer(&mut recorder);
+            for _ in 0..100_000 {
+                out.println(123_456_789u32);
+            }
+        }
+        let total: usize = recorder.writes.iter().sum();
+        assert_eq!(total, 1_000_000);
+        assert!(recorder.writes.len() > 1 && recorder.writes.len() < 30);
+    }
+
+    #[test]
+    fn test_writer_flush_writes_pending_output() {
+        let mut recorder = Recorder::default();
+        let mut out = Writer::from_writer(&mut recorder);
+        out.println(1);
+        out.flush();
+        out.println(2);
+        drop(out);
+        assert_eq!(recorder.writes, vec![2, 2]);
+    }
+}
@@ -0,0 +1,164 @@
+#[cfg(test)]
+mod tests {
+    use cp_lib::input;
+    use cp_lib::io::scanner::Scanner;
+    use cp_lib::io::writer::Writer;
+    use cp_lib::math::modint::{Barrett, DynModInt, Mint107, Mint998, ModInt};
+
+    /// xorshift64, so the property tests are reproducible without a crate.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+    }
+
+    /// Operands biased towards the edges of `[0, m)`.
+    fn operand(rng: &mut Rng, m: u64) -> u64 {
+        match rng.next() % 4 {
+            0 => rng.next() % 3.min(m),
+            1 => m - 1 - rng.next() % 3.min(m),
+            _ => rng.next() % m,
+        }
+    }
+
+    fn naive_pow(base: u64, exp: u64, m: u64) -> u64 {
+        (0..exp).fold(1 % m, |acc, _| acc * base % m)
+    }
+
+    fn check_static<const M: u32>(seed: u64) {
+        let m = M as u64;
+        let mut rng = Rng(seed);
+        for _ in 0..10_000 {
+            let (a, b) = (operand(&mut rng, m), operand(&mut rng, m));
+            let (x, y) = (ModInt::<M>::from(a), ModInt::<M>::from(b));
+            assert_eq!((x + y).value() as u64, (a + b) % m);
+            assert_eq!((x - y).value() as u64, (a + m - b) % m);
+            assert_eq!((x * y).value() as u64, a * b % m);
+            assert_eq!((-x).value() as u64, (m - a) % m);
+            if b != 0 {
+                assert_eq!((x / y * y), x);
+            }
+            let e = rng.next() % 64;
+            assert_eq!(x.pow(e).value() as u64, naive_pow(a, e, m));
+        }
+    }
+
+    #[test]
+    fn test_static_matches_naive() {
+        check_static::<998_244_353>(1);
+        check_static::<1_000_000_007>(2);
+        check_static::<{ u32::MAX - 4 }>(3);
+        check_static::<2>(4);
+    }
+
+    #[test]
+    fn test_dynamic_matches_naive() {
+        for (seed, m) in [(5u64, 998_244_353u64), (6, 1_000_000_007), (7, 12), (8, (1 << 31) - 1), (9, 2)] {
+            DynModInt::set_modulus(m as u32);
+            let mut rng = Rng(seed);
+            for _ in 0..10_000 {
+                let (a, b) = (operand(&mut rng, m), operand(&mut rng, m));
+                let (x, y) = (DynModInt::from(a), DynModInt::from(b));
+                assert_eq!((x + y).value() as u64, (a + b) % m);
+                assert_eq!((x - y).value() as u64, (a + m - b) % m);
+                assert_eq!((x * y).value() as u64, a * b % m, "{} * {} mod {}", a, b, m);
+                let e = rng.next() % 64;
+                assert_eq!(x.pow(e).value() as u64, naive_pow(a, e, m));
+            }
+        }
+    }
+
+    #[test]
+    fn test_barrett_modulus_one() {
+        let barrett = Barrett::new(1);
+        assert_eq!(barrett.mul(0, 0), 0);
+        DynModInt::set_modulus(1);
+        assert_eq!((DynModInt::new(5) * DynModInt::new(7)).value(), 0);
+        assert_eq!(DynModInt::new(3).pow(0).value(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Barrett reduction needs")]
+    fn test_barrett_rejects_large_modulus() {
+        let _ = Barrett::new(1 << 31);
+    }
+
+    #[test]
+    fn test_dynamic_inverse_non_prime_modulus() {
+        DynModInt::set_modulus(10);
+        assert_eq!(DynModInt::new(3).inv().value(), 7);
+        assert_eq!((DynModInt::new(9) / 3).value(), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "has no inverse")]
+    fn test_inverse_of_non_coprime_panics() {
+        DynModInt::set_modulus(10);
+        let _ = DynModInt::new(4).inv();
+    }
+
+    #[test]
+    fn test_conversions() {
+        assert_eq!(Mint998::new(-1).value(), 998_244_352);
+        assert_eq!(Mint107::from(-1_000_000_008i64).value(), 1_000_000_006);
+        assert_eq!(Mint107::from(u128::MAX).value(), (u128::MAX % 1_000_000_007) as u32);
+        assert_eq!(Mint107::from(i128::MIN).value(), i128::MIN.rem_euclid(1_000_000_007) as u32);
+        assert_eq!(Mint107::from(1_000_000_007usize), Mint107::default());
+    }
+
+    #[test]
+    fn test_operators_with_integers() {
+        let mut x = Mint998::new(5);
+        x += 3;
+        x *= 2u64;
+        x -= 20;
+        assert_eq!(x, Mint998::new(-4));
+        x /= 2;
+        assert_eq!(x, Mint998::new(-2));
+        assert_eq!(Mint998::new(1) / 2 * 2, Mint998::new(1));
+        assert_eq!(Mint998::new(2).inv().value(), 499_122_177);
+        assert_eq!(Mint107::new(2).pow(1_000_000_006).value(), 1);
+        assert_eq!(Mint998::new(0).pow(0).value(), 1);
+    }
+
+    #[test]
+    fn test_sum_and_product() {
+        let values: Vec<Mint107> = (1..=20).map(Mint107::from).collect();
+        let sum: Mint107 = values.iter().sum();
+        let product: Mint107 = values.into_iter().product();
+        assert_eq!(sum.value(), 210);
+        assert_eq!(product.value(), (1..=20u64).fold(1, |acc, x| acc * x % 1_000_000_007) as u32);
+        assert_eq!(std::iter::empty::<Mint107>().product::<Mint107>().value(), 1);
+    }
+
+    #[test]
+    fn test_display_and_parse() {
+        assert_eq!(Mint998::new(-1).to_string(), "998244352");
+        assert_eq!(format!("{:?}", vec![Mint107::new(1), Mint107::new(2)]), "[1, 2]");
+        assert_eq!("-1".parse::<Mint107>().unwrap().value(), 1_000_000_006);
+        assert_eq!("100000000000000000000".parse::<Mint107>().unwrap().value(), (100_000_000_000_000_000_000u128 % 1_000_000_007) as u32);
+        assert!("abc".parse::<Mint107>().is_err());
+
+        let mut out = Writer::in_memory();
+        out.println(Mint998::new(-1));
+        out.println_spaced([Mint107::new(1), Mint107::new(-2)]);
+        DynModInt::set_modulus(7);
+        out.println(DynModInt::new(-1));
+        assert_eq!(out.contents(), "998244352\n1 1000000005\n6\n");
+
+        let mut sc = Scanner::from_string("1000000008 -1\n3\n1 2 3\n".to_string());
+        assert_eq!(sc.next::<Mint107>().value(), 1);
+        assert_eq!(sc.next::<Mint998>().value(), 998_244_352);
+        input! {
+            from sc,
+            n: usize,
+            a: [Mint998; n],
+        }
+        assert_eq!(a.iter().sum::<Mint998>().value(), 6);
+    }
+}
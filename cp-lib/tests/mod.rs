@@ -1 +1,4 @@
-pub mod io;
\ No newline at end of file
+pub mod algorithms;
+pub mod debug;
+pub mod io;
+pub mod math;
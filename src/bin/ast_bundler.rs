@@ -4,42 +4,843 @@
  * License: MIT
  */
 
-use bundler_lib::{BundlerConfig, CodeBundler};
-use std::path::PathBuf;
+use bundler_lib::checker::{Checker, CheckerSpec};
+use bundler_lib::companion;
+use bundler_lib::interactive::{self, InteractiveOptions};
+use bundler_lib::limits;
+use bundler_lib::problems::{self, BundleReport, BundleStatus, Problem};
+use bundler_lib::project_config;
+use bundler_lib::runner::{self, Verdict};
+use bundler_lib::samples;
+use bundler_lib::scaffold;
+use bundler_lib::stress::{self, StressBinaries, StressOptions, StressOutcome};
+use bundler_lib::{BundlerConfig, CodeBundler, JudgeProfile, LibCrate, ProjectConfig, DEFAULT_CRATE};
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+
+const USAGE: &str = "Usage: ast_bundler [options] [problem...]
+       ast_bundler new <ID> [--name <title>] [--main <path>] [--template <path>] [--no-archive]
+       ast_bundler import <page.html> [--id <ID>] [--name <title>] [--main <path>]
+       ast_bundler listen [--port <n>] [--once]
+       ast_bundler test [problem] [options]
+       ast_bundler stress <solution> <brute> <generator> [--iterations <n>] [--time <secs>] [--jobs <n>] [--seed <n>]
+
+Without problems the configured main file is bundled. A problem is a file name
+(`a`), a path below a problem directory (`cf1900/a`) or a path to a file.
+
+`new` moves the current solution into the archive directory and writes a fresh
+one from the template with `_ID` and `_PROBLEM` filled in.
+
+`import` reads the samples, ID and title from a saved Codeforces or AtCoder
+problem page, writes them to `tests/<ID>/sample*.in/.out` and creates the
+solution like `new`.
+
+`listen` waits for problems sent by the Competitive Companion browser extension
+and sets each one up like `import`.
+
+`test` bundles the solution, compiles the bundle and runs it on every
+`tests/<ID>/*.in`, comparing against the matching `*.out`.
+
+Options:
+    --all                Bundle every problem in the problem directories
+    --config <path>      Use this cp-rs.toml instead of searching for one
+    --main <path>        Solution file to bundle
+    --lib-root <path>    Library source root of cp_lib
+    --crate <name=path>  Flatten another library crate from its source root (repeatable)
+    --output-dir <path>  Directory for bundled files
+    --naming <template>  Output filename template ({id}, {problem}, {timestamp})
+    --edition <year>     Edition used to verify the bundle
+    --time-limit <secs>  Judge time limit per test for `test`
+    --memory-limit <mb>  Judge memory limit per test for `test`
+    --cfg <flag>         Enable a cfg flag on the judge (repeatable)
+    --minify             Strip doc comments and compact the output
+    --no-discover        Do not pick up path dependencies from Cargo.toml
+    --no-verify          Skip compiling the bundle with rustc
+    --name <title>       Problem title for `new`, e.g. \"D2. Magic Powder\"
+    --id <ID>            Problem ID for `import` when the page does not show one
+    --port <n>           Port for `listen` (default 10043)
+    --once               Stop `listen` after the first problem
+    --template <path>    Solution template for `new`
+    --no-archive         Overwrite the solution without archiving it
+    --tests-dir <path>   Directory with the per-problem test folders for `test`
+    --checker <checker>  tokens, float, unordered, yesno or a checker source file
+    --interactor <path>  Interactor source file or binary for interactive problems
+    --queries <n>        Query limit for interactive problems
+    --timeout <secs>     Wall-clock limit per interactive run (default 5)
+    --iterations <n>     Stop `stress` after this many runs
+    --time <secs>        Time budget for `stress` (default 60); each run gets the judge time limit or 3 s
+    --jobs <n>           Parallel `stress` runs (default: available cores)
+    --seed <n>           First generator seed for `stress` (default 1)
+    -h, --help           Print this help";
+
+#[derive(Default, PartialEq)]
+enum Command {
+    #[default]
+    Bundle,
+    New,
+    Import,
+    Listen,
+    Test,
+    Stress,
+}
+
+/// Command line flags; anything set here overrides cp-rs.toml.
+#[derive(Default)]
+struct CliArgs {
+    command: Command,
+    config: Option<PathBuf>,
+    main_file: Option<PathBuf>,
+    lib_root: Option<PathBuf>,
+    crates: Vec<LibCrate>,
+    output_dir: Option<PathBuf>,
+    naming: Option<String>,
+    edition: Option<String>,
+    cfg: Vec<String>,
+    minify: bool,
+    all: bool,
+    problems: Vec<String>,
+    no_discover: bool,
+    no_verify: bool,
+    name: Option<String>,
+    id: Option<String>,
+    port: Option<u16>,
+    once: bool,
+    template: Option<PathBuf>,
+    no_archive: bool,
+    tests_dir: Option<PathBuf>,
+    iterations: Option<u64>,
+    time: Option<u64>,
+    jobs: Option<usize>,
+    seed: Option<u64>,
+    checker: Option<String>,
+    interactor: Option<PathBuf>,
+    queries: Option<usize>,
+    timeout: Option<std::time::Duration>,
+    time_limit: Option<f64>,
+    memory_limit: Option<u64>,
+}
+
+fn parse_args(args: impl Iterator<Item = String>) -> Result<CliArgs, String> {
+    let mut cli = CliArgs::default();
+    let mut args = args.peekable();
+    match args.peek().map(String::as_str) {
+        Some("new") => cli.command = Command::New,
+        Some("import") => cli.command = Command::Import,
+        Some("listen") => cli.command = Command::Listen,
+        Some("test") => cli.command = Command::Test,
+        Some("stress") => cli.command = Command::Stress,
+        _ => {}
+    }
+    if cli.command != Command::Bundle {
+        args.next();
+    }
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .ok_or_else(|| format!("missing value for {}", name))
+        };
+        match arg.as_str() {
+            "--config" => cli.config = Some(PathBuf::from(value("--config")?)),
+            "--main" => cli.main_file = Some(PathBuf::from(value("--main")?)),
+            "--lib-root" => cli.lib_root = Some(PathBuf::from(value("--lib-root")?)),
+            "--crate" => {
+                let spec = value("--crate")?;
+                let (name, root) = spec
+                    .split_once('=')
+                    .ok_or_else(|| format!("expected <name=path> for --crate, got '{}'", spec))?;
+                cli.crates.push(LibCrate::new(name, Path::new(root)));
+            }
+            "--output-dir" => cli.output_dir = Some(PathBuf::from(value("--output-dir")?)),
+            "--naming" => cli.naming = Some(value("--naming")?),
+            "--edition" => cli.edition = Some(value("--edition")?),
+            "--time-limit" => {
+                let secs = parse_number("--time-limit", value("--time-limit")?)?;
+                project_config::seconds("--time-limit", secs)?;
+                cli.time_limit = Some(secs);
+            }
+            "--memory-limit" => {
+                let mb = parse_number("--memory-limit", value("--memory-limit")?)?;
+                project_config::megabytes("--memory-limit", mb)?;
+                cli.memory_limit = Some(mb);
+            }
+            "--cfg" => cli.cfg.push(value("--cfg")?),
+            "--minify" => cli.minify = true,
+            "--all" => cli.all = true,
+            "--no-discover" => cli.no_discover = true,
+            "--no-verify" => cli.no_verify = true,
+            "--name" => cli.name = Some(value("--name")?),
+            "--id" => cli.id = Some(value("--id")?),
+            "--port" => cli.port = Some(parse_number("--port", value("--port")?)?),
+            "--once" => cli.once = true,
+            "--template" => cli.template = Some(PathBuf::from(value("--template")?)),
+            "--no-archive" => cli.no_archive = true,
+            "--tests-dir" => cli.tests_dir = Some(PathBuf::from(value("--tests-dir")?)),
+            "--iterations" => cli.iterations = Some(parse_number("--iterations", value("--iterations")?)?),
+            "--time" => cli.time = Some(parse_number("--time", value("--time")?)?),
+            "--jobs" => cli.jobs = Some(parse_number("--jobs", value("--jobs")?)?),
+            "--checker" => cli.checker = Some(value("--checker")?),
+            "--interactor" => cli.interactor = Some(PathBuf::from(value("--interactor")?)),
+            "--queries" => cli.queries = Some(parse_number("--queries", value("--queries")?)?),
+            "--timeout" => {
+                let secs = parse_number("--timeout", value("--timeout")?)?;
+                cli.timeout = Some(project_config::seconds("--timeout", secs)?);
+            }
+            "--seed" => cli.seed = Some(parse_number("--seed", value("--seed")?)?),
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                std::process::exit(0);
+            }
+            other if !other.starts_with('-') => cli.problems.push(other.to_string()),
+            other => return Err(format!("unknown argument '{}'\n\n{}", other, USAGE)),
+        }
+    }
+
+    if cli.command == Command::New && cli.problems.len() != 1 {
+        return Err(format!("`new` expects exactly one problem ID\n\n{}", USAGE));
+    }
+    if cli.command == Command::Import && cli.problems.len() != 1 {
+        return Err(format!("`import` expects exactly one saved problem page\n\n{}", USAGE));
+    }
+    if cli.command == Command::Listen && !cli.problems.is_empty() {
+        return Err(format!("`listen` takes no problems\n\n{}", USAGE));
+    }
+    if cli.command == Command::Test && cli.problems.len() > 1 {
+        return Err(format!("`test` takes at most one problem\n\n{}", USAGE));
+    }
+    if cli.command == Command::Stress && cli.problems.len() != 3 {
+        return Err(format!("`stress` expects <solution> <brute> <generator>\n\n{}", USAGE));
+    }
+
+    Ok(cli)
+}
+
+fn parse_number<T: std::str::FromStr>(name: &str, value: String) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("expected a number for {}, got '{}'", name, value))
+}
+
+fn load_config(cli: &CliArgs) -> Result<BundlerConfig, Box<dyn std::error::Error>> {
+    let cwd = std::env::current_dir()?;
+    let config_path = match &cli.config {
+        Some(path) => Some(path.clone()),
+        None => ProjectConfig::discover(&cwd),
+    };
+
+    let mut config = match config_path {
+        Some(path) => {
+            println!("Using config: {}", path.display());
+            ProjectConfig::load_bundler_config(&path, &cwd)?
+        }
+        None => BundlerConfig::default(),
+    };
+
+    if let Some(path) = &cli.main_file {
+        config.main_file = path.clone();
+    }
+    if let Some(path) = &cli.lib_root {
+        config.crates.retain(|existing| existing.name != DEFAULT_CRATE);
+        config.lib_root = Some(path.clone());
+    }
+    if cli.no_discover {
+        config.discover_crates = false;
+    }
+    for krate in &cli.crates {
+        config.crates.retain(|existing| existing.name != krate.name);
+        config.crates.push(krate.clone());
+    }
+    if let Some(path) = &cli.output_dir {
+        config.output_dir = path.clone();
+    }
+    if let Some(naming) = &cli.naming {
+        config.naming = Some(naming.clone());
+    }
+    if let Some(edition) = &cli.edition {
+        config.judge.edition = edition.clone();
+    }
+    if cli.time_limit.is_some() {
+        config.judge.time_limit = cli.time_limit;
+    }
+    if cli.memory_limit.is_some() {
+        config.judge.memory_limit = cli.memory_limit;
+    }
+    config.cfg.extend(cli.cfg.iter().cloned());
+    if cli.minify {
+        config.minify.strip_doc_comments = true;
+        config.minify.compact = true;
+    }
+    if let Some(template) = &cli.template {
+        config.scaffold.template = Some(template.clone());
+    }
+    if cli.no_archive {
+        config.scaffold.archive = false;
+    }
+    if let Some(dir) = &cli.tests_dir {
+        config.test.tests_dir = dir.clone();
+    }
+    if let Some(spec) = &cli.checker {
+        config.test.checker = Some(CheckerSpec::parse(spec));
+    }
+    if let Some(path) = &cli.interactor {
+        config.test.interactor = Some(path.clone());
+    }
+    if cli.queries.is_some() {
+        config.test.query_limit = cli.queries;
+    }
+    if let Some(timeout) = cli.timeout {
+        config.test.timeout = timeout;
+    }
+
+    Ok(config)
+}
+
+fn check_rustc_version(judge: &JudgeProfile) {
+    let Some(expected) = &judge.rustc_version else {
+        return;
+    };
+    let Ok(output) = std::process::Command::new("rustc").arg("--version").output() else {
+        return;
+    };
+    let local = String::from_utf8_lossy(&output.stdout);
+    if !local.contains(expected.as_str()) {
+        println!(
+            "WARNING: Judge uses rustc {}, local compiler is {}",
+            expected,
+            local.trim()
+        );
+    }
+}
+
+/// Runs `bundler`, printing its warnings to stderr.
+fn bundle(bundler: &mut CodeBundler) -> Result<String, Box<dyn std::error::Error>> {
+    let result = bundler.bundle();
+    for warning in bundler.warnings() {
+        eprintln!("WARNING: {}", warning);
+    }
+    result
+}
+
+/// Compiles a bundle with the judge's edition; the binary is removed afterwards.
+fn verify_bundle(bundled_path: &Path, judge: &JudgeProfile) -> std::io::Result<bool> {
+    let stem = bundled_path.file_stem().unwrap_or_default().to_string_lossy();
+    let test_binary = bundled_path.with_file_name(format!("{}_test", stem));
+    let compile_result = std::process::Command::new("rustc")
+        .arg(bundled_path)
+        .arg("-o")
+        .arg(&test_binary)
+        .args(["--edition", &judge.edition, "--allow", "warnings"])
+        .status()?;
+
+    // Clean up test binary
+    let _ = std::fs::remove_file(&test_binary);
+    Ok(compile_result.success())
+}
+
+/// Archives the current solution and writes a fresh one for `cli.problems[0]`.
+fn new_problem(cli: &CliArgs, config: &BundlerConfig) -> i32 {
+    create_solution(config, &cli.problems[0], cli.name.as_deref())
+}
+
+fn create_solution(config: &BundlerConfig, id: &str, name: Option<&str>) -> i32 {
+    match scaffold::new_problem(&config.main_file, id, name, &config.scaffold) {
+        Ok(archived) => {
+            if let Some(path) = archived {
+                println!("Archived previous solution: {}", path.display());
+            }
+            println!("Created {} for problem {}", config.main_file.display(), id);
+            0
+        }
+        Err(e) => {
+            eprintln!("ERROR: Could not create solution: {}", e);
+            1
+        }
+    }
+}
+
+/// Writes the samples of a saved problem page to its tests directory and
+/// creates the solution for it.
+fn import_problem(cli: &CliArgs, config: &BundlerConfig) -> i32 {
+    let page_path = Path::new(&cli.problems[0]);
+    let page = match std::fs::read_to_string(page_path)
+        .map_err(|e| e.to_string())
+        .and_then(|html| samples::parse_page(&html))
+    {
+        Ok(page) => page,
+        Err(e) => {
+            eprintln!("ERROR: {}: {}", page_path.display(), e);
+            return 1;
+        }
+    };
+    let Some(id) = cli.id.clone().or(page.id) else {
+        eprintln!("ERROR: {} shows no problem ID, pass --id", page_path.display());
+        return 2;
+    };
+    let name = cli.name.clone().or(page.name);
+
+    let tests_dir = config.tests_dir_for(&id);
+    if let Err(e) = samples::write_samples(&tests_dir, &page.samples) {
+        eprintln!("ERROR: Could not write samples to {}: {}", tests_dir.display(), e);
+        return 1;
+    }
+    println!("Wrote {} sample(s) to {}", page.samples.len(), tests_dir.display());
+
+    create_solution(config, &id, name.as_deref())
+}
+
+/// Receives problems from Competitive Companion and sets up their tests and
+/// solution, until interrupted or, with `--once`, after the first one.
+fn listen(cli: &CliArgs, config: &BundlerConfig) -> i32 {
+    let port = cli.port.unwrap_or(companion::DEFAULT_PORT);
+    let listener = match std::net::TcpListener::bind(("127.0.0.1", port)) {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("ERROR: Could not listen on port {}: {}", port, e);
+            return 1;
+        }
+    };
+    println!("Listening for Competitive Companion on port {}...", port);
+
+    let mut failures = 0;
+    loop {
+        let task = match companion::accept(&listener) {
+            Ok(task) => {
+                failures = 0;
+                task
+            }
+            Err(e) => {
+                eprintln!("WARNING: Ignored request: {}", e);
+                // Back off, so a lasting accept error (e.g. out of file descriptors)
+                // does not turn this into a busy loop.
+                failures += 1;
+                std::thread::sleep(std::time::Duration::from_millis(100 * failures.min(10)));
+                continue;
+            }
+        };
+        let id = task.id();
+        println!("\nReceived {} ({})", task.name, task.group);
+
+        let tests_dir = config.tests_dir_for(&id);
+        if let Err(e) = samples::write_samples(&tests_dir, &task.tests) {
+            eprintln!("ERROR: Could not write samples to {}: {}", tests_dir.display(), e);
+            return 1;
+        }
+        println!("Wrote {} sample(s) to {}", task.tests.len(), tests_dir.display());
+
+        print_limits(&task.limits());
+        if task.interactive {
+            println!("Interactive problem: add an interactor to {}", tests_dir.display());
+        }
+
+        let code = create_solution(config, &id, Some(&task.name));
+        if code != 0 || cli.once {
+            return code;
+        }
+    }
+}
+
+fn print_limits(limits: &limits::Limits) {
+    let shown: Vec<String> = [
+        limits.time.map(|time| format!("{} ms", time.as_millis())),
+        limits.memory.map(limits::format_memory),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    if !shown.is_empty() {
+        println!("Limits: {}", shown.join(", "));
+    }
+}
+
+/// Bundles the solution, compiles the bundle and runs it on the problem's
+/// sample tests. Returns the exit code.
+fn test_problem(cli: &CliArgs, config: &BundlerConfig) -> i32 {
+    let config = match cli.problems.first() {
+        Some(selector) => match problems::select_problems(&config.problems(), std::slice::from_ref(selector)) {
+            Ok(selected) => config.for_problem(&selected[0]),
+            Err(e) => {
+                eprintln!("ERROR: {}", e);
+                return 2;
+            }
+        },
+        None => config.clone(),
+    };
+
+    let id = match config.problem_id() {
+        Ok(Some(id)) => id,
+        Ok(None) => {
+            eprintln!("ERROR: {} declares no _ID, cannot locate its tests", config.main_file.display());
+            return 2;
+        }
+        Err(e) => {
+            eprintln!("ERROR: {}", e);
+            return 1;
+        }
+    };
+    let tests_dir = config.tests_dir_for(&id);
+    let cases = runner::discover_tests(&tests_dir);
+    if cases.is_empty() {
+        eprintln!("ERROR: No *.in files in {}", tests_dir.display());
+        return 1;
+    }
+
+    let bundled_path = match bundle(&mut CodeBundler::new(config.clone())) {
+        Ok(filename) => config.output_dir.join(filename),
+        Err(e) => {
+            eprintln!("ERROR: Bundling failed: {}", e);
+            return 1;
+        }
+    };
+
+    // Test the artifact that will be submitted, not the local build.
+    check_rustc_version(&config.judge);
+    let stem = bundled_path.file_stem().unwrap_or_default().to_string_lossy();
+    let binary = bundled_path.with_file_name(format!("{}_run", stem));
+    if let Err(e) = runner::compile(&bundled_path, &binary, &config.judge) {
+        eprintln!("ERROR: Bundled code does not compile:\n{}", e);
+        return 1;
+    }
+
+    let interactor = match load_interactor(&config, &tests_dir) {
+        Ok(interactor) => interactor,
+        Err(e) => {
+            eprintln!("ERROR: {}", e);
+            return 1;
+        }
+    };
+    let checker = match interactor {
+        Some(_) => Checker::default(),
+        None => match load_checker(&config, Some(&tests_dir)) {
+            Ok(checker) => checker,
+            Err(e) => {
+                eprintln!("ERROR: {}", e);
+                return 1;
+            }
+        },
+    };
+
+    let limits = config.judge.limits();
+    println!("\nRunning {} test(s) from {}", cases.len(), tests_dir.display());
+    print_limits(&limits);
+    let color = std::env::var_os("NO_COLOR").is_none() && std::io::stdout().is_terminal();
+    let name_width = cases.iter().map(|c| c.name.len()).max().unwrap_or(0);
+    let mut passed = 0;
+    let mut failed = 0;
+    for case in &cases {
+        let outcome = match &interactor {
+            Some(interactor) => run_interactive_case(&binary, interactor, case, &config)
+                .map(|(result, transcript)| (result, Some(transcript))),
+            None => runner::run_case(&binary, case, &checker, &limits).map(|result| (result, None)),
+        };
+        let (result, transcript) = match outcome {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                eprintln!("ERROR: {}: {}", case.name, e);
+                failed += 1;
+                continue;
+            }
+        };
+        println!("{}", runner::format_case(&result, name_width, color));
+        if let Some(transcript) = transcript.filter(|_| result.verdict.is_failure()) {
+            // The end of the conversation is usually where it went wrong.
+            let lines: Vec<&str> = transcript.lines().collect();
+            for line in &lines[lines.len().saturating_sub(10)..] {
+                println!("    {}", line);
+            }
+        }
+        match result.verdict {
+            Verdict::Accepted => passed += 1,
+            ref verdict if verdict.is_failure() => failed += 1,
+            _ => {}
+        }
+    }
+    let _ = std::fs::remove_file(&binary);
+
+    println!("\nPassed {}/{}", passed, cases.len());
+    if failed > 0 { 1 } else { 0 }
+}
+
+/// The configured interactor, falling back to `interactor.rs` in the problem's
+/// test directory. Sources are bundled and compiled, anything else is run as is.
+fn load_interactor(config: &BundlerConfig, tests_dir: &Path) -> Result<Option<PathBuf>, Box<dyn std::error::Error>> {
+    let local = tests_dir.join("interactor.rs");
+    let Some(path) = config.test.interactor.clone().or(local.is_file().then_some(local)) else {
+        return Ok(None);
+    };
+    if path.extension().is_none_or(|ext| ext != "rs") {
+        return Ok(Some(path));
+    }
+
+    println!("\n== interactor ({}) ==", path.display());
+    let problem = Problem {
+        name: "interactor".to_string(),
+        path,
+    };
+    Ok(Some(build_helper(config, &problem, "interactive", "interactor")?))
+}
+
+/// Runs one interactive case and saves its transcript next to the interactor.
+fn run_interactive_case(
+    binary: &Path,
+    interactor: &Path,
+    case: &runner::TestCase,
+    config: &BundlerConfig,
+) -> std::io::Result<(runner::CaseResult, String)> {
+    let options = InteractiveOptions {
+        timeout: config.test.timeout,
+        query_limit: config.test.query_limit,
+    };
+    let result = interactive::run_interactive(binary, interactor, &case.input, &options)?;
+
+    let transcript = result.transcript.to_string();
+    let log_dir = config.output_dir.join("interactive");
+    std::fs::create_dir_all(&log_dir)?;
+    std::fs::write(log_dir.join(format!("{}.log", case.name)), &transcript)?;
+
+    let case_result = runner::CaseResult {
+        name: case.name.clone(),
+        verdict: result.verdict,
+        elapsed: result.elapsed,
+        usage: None,
+    };
+    Ok((case_result, transcript))
+}
+
+/// Bundles a helper program into `<output-dir>/<dir>/<dir>_<role>.rs` and compiles it.
+fn build_helper(config: &BundlerConfig, problem: &Problem, dir: &str, role: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let helper_dir = config.output_dir.join(dir);
+    let role_config = BundlerConfig {
+        output_dir: helper_dir.clone(),
+        naming: Some(format!("{}_{}.rs", dir, role)),
+        ..config.for_problem(problem)
+    };
+    let filename = bundle(&mut CodeBundler::new(role_config))?;
+    let binary = helper_dir.join(format!("{}_{}", dir, role));
+    runner::compile(&helper_dir.join(filename), &binary, &config.judge)
+        .map_err(|e| format!("{} does not compile:\n{}", problem.path.display(), e))?;
+    Ok(binary)
+}
+
+/// The configured checker, falling back to `checker.rs` in the problem's test
+/// directory; checker sources are bundled and compiled with cp-lib.
+fn load_checker(config: &BundlerConfig, tests_dir: Option<&Path>) -> Result<Checker, Box<dyn std::error::Error>> {
+    let local = tests_dir
+        .map(|dir| dir.join("checker.rs"))
+        .filter(|path| path.is_file())
+        .map(CheckerSpec::Source);
+    let tolerance = config.test.float_tolerance;
+
+    match config.test.checker.clone().or(local) {
+        None => Ok(Checker::Tokens { tolerance }),
+        Some(CheckerSpec::Builtin(name)) => {
+            Checker::builtin(&name, tolerance).ok_or_else(|| format!("unknown checker '{}'", name).into())
+        }
+        Some(CheckerSpec::Source(path)) => {
+            println!("\n== checker ({}) ==", path.display());
+            let problem = Problem {
+                name: "checker".to_string(),
+                path,
+            };
+            Ok(Checker::Program(build_helper(config, &problem, "checker", "program")?))
+        }
+    }
+}
+
+/// Runs solution and brute force on generated inputs until they disagree.
+/// Returns the exit code.
+fn stress_problem(cli: &CliArgs, config: &BundlerConfig) -> i32 {
+    let selected = match problems::select_problems(&config.problems(), &cli.problems) {
+        Ok(selected) => selected,
+        Err(e) => {
+            eprintln!("ERROR: {}", e);
+            return 2;
+        }
+    };
+
+    let mut binaries = Vec::with_capacity(3);
+    for (problem, role) in selected.iter().zip(["solution", "brute", "generator"]) {
+        println!("\n== {} ({}) ==", role, problem.path.display());
+        match build_helper(config, problem, "stress", role) {
+            Ok(binary) => binaries.push(binary),
+            Err(e) => {
+                eprintln!("ERROR: {}", e);
+                return 1;
+            }
+        }
+    }
+    let [solution, brute, generator] = <[PathBuf; 3]>::try_from(binaries).expect("three binaries");
+    let binaries = StressBinaries { solution, brute, generator };
+    let checker = match load_checker(config, None) {
+        Ok(checker) => checker,
+        Err(e) => {
+            eprintln!("ERROR: {}", e);
+            return 1;
+        }
+    };
+
+    // Runs are held to the judge's limits, so an endless loop is a failure.
+    let judge_limits = config.judge.limits();
+    let run_limits = limits::Limits {
+        time: judge_limits.time.or(Some(stress::DEFAULT_RUN_TIME_LIMIT)),
+        ..judge_limits
+    };
+    let defaults = StressOptions::default();
+    let options = StressOptions {
+        seed: cli.seed.unwrap_or(defaults.seed),
+        iterations: cli.iterations,
+        // An iteration limit alone runs to completion.
+        time_budget: match (cli.time, cli.iterations) {
+            (Some(secs), _) => Some(std::time::Duration::from_secs(secs)),
+            (None, Some(_)) => None,
+            (None, None) => defaults.time_budget,
+        },
+        jobs: cli
+            .jobs
+            .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get())),
+        checker,
+        limits: run_limits,
+    };
+
+    println!("\nStress testing with {} job(s) from seed {}...", options.jobs, options.seed);
+    match stress::stress(&binaries, &options) {
+        Ok(StressOutcome::Passed { runs }) => {
+            println!("No mismatch in {} runs", runs);
+            0
+        }
+        Ok(StressOutcome::Failed(failure)) => {
+            let failure_dir = config.output_dir.join("stress").join("failure");
+            println!("Mismatch on seed {}: {}", failure.seed, failure.reason);
+            println!("--- input ---\n{}", failure.input.trim_end());
+            println!("--- solution ---\n{}", failure.solution_output.trim_end());
+            println!("--- brute ---\n{}", failure.brute_output.trim_end());
+            match failure.save(&failure_dir) {
+                Ok(()) => println!("Saved to {}", failure_dir.display()),
+                Err(e) => eprintln!("ERROR: Could not save failing case: {}", e),
+            }
+            1
+        }
+        Err(e) => {
+            eprintln!("ERROR: {}", e);
+            1
+        }
+    }
+}
+
+/// Bundles the selected problems and prints a summary table. Returns the exit code.
+fn bundle_problems(cli: &CliArgs, config: &BundlerConfig) -> i32 {
+    let available = config.problems();
+    let selected = if cli.all {
+        available
+    } else {
+        match problems::select_problems(&available, &cli.problems) {
+            Ok(selected) => selected,
+            Err(e) => {
+                eprintln!("ERROR: {}", e);
+                return 2;
+            }
+        }
+    };
+    if selected.is_empty() {
+        eprintln!("ERROR: No problems found in {:?}", config.problem_dirs);
+        return 1;
+    }
+    if !cli.no_verify {
+        check_rustc_version(&config.judge);
+    }
+
+    let mut reports = Vec::with_capacity(selected.len());
+    for problem in &selected {
+        println!("\n== {} ({}) ==", problem.name, problem.path.display());
+        let problem_config = config.for_problem(problem);
+        let mut bundler = CodeBundler::new(problem_config.clone());
+
+        let report = match bundle(&mut bundler) {
+            Ok(filename) => {
+                let bundled_path = problem_config.output_dir.join(&filename);
+                let size = std::fs::metadata(&bundled_path).map(|m| m.len() as usize).unwrap_or(0);
+                let status = if cli.no_verify {
+                    BundleStatus::Unverified
+                } else {
+                    match verify_bundle(&bundled_path, &config.judge) {
+                        Ok(true) => BundleStatus::Verified,
+                        Ok(false) => BundleStatus::CompileError,
+                        Err(e) => BundleStatus::Failed(format!("rustc: {}", e)),
+                    }
+                };
+                BundleReport {
+                    problem: problem.name.clone(),
+                    output: Some(filename),
+                    size,
+                    status,
+                    over_size_limit: config.judge.size_limit.is_some_and(|limit| size > limit),
+                }
+            }
+            Err(e) => BundleReport {
+                problem: problem.name.clone(),
+                output: None,
+                size: 0,
+                status: BundleStatus::Failed(e.to_string()),
+                over_size_limit: false,
+            },
+        };
+        reports.push(report);
+    }
+
+    println!("\n{}", problems::format_summary(&reports));
+    let failed = reports
+        .iter()
+        .any(|r| matches!(r.status, BundleStatus::CompileError | BundleStatus::Failed(_)));
+    if failed { 1 } else { 0 }
+}
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("AST-based Code Bundler v0.1.0");
 
-    let config = BundlerConfig {
-        main_file: PathBuf::from("src/main.rs"),
-        lib_root: PathBuf::from("cp-lib/src"),
-        output_dir: PathBuf::from("bundled"),
-        create_versioned_copy: true,
+    let cli = match parse_args(std::env::args().skip(1)) {
+        Ok(cli) => cli,
+        Err(e) => {
+            eprintln!("ERROR: {}", e);
+            std::process::exit(2);
+        }
+    };
+
+    let config = match load_config(&cli) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("ERROR: Invalid config: {}", e);
+            std::process::exit(1);
+        }
     };
+    match cli.command {
+        Command::New => std::process::exit(new_problem(&cli, &config)),
+        Command::Import => std::process::exit(import_problem(&cli, &config)),
+        Command::Listen => std::process::exit(listen(&cli, &config)),
+        Command::Test => std::process::exit(test_problem(&cli, &config)),
+        Command::Stress => std::process::exit(stress_problem(&cli, &config)),
+        Command::Bundle => {}
+    }
+    if cli.all || !cli.problems.is_empty() {
+        std::process::exit(bundle_problems(&cli, &config));
+    }
+
+    let output_dir = config.output_dir.clone();
+    let judge = config.judge.clone();
 
     let mut bundler = CodeBundler::new(config);
 
-    match bundler.bundle() {
+    match bundle(&mut bundler) {
         Ok(filename) => {
             println!("Bundle complete: {}", filename);
 
+            if cli.no_verify {
+                return Ok(());
+            }
+
             // Verify the bundled code compiles
             println!("Verifying bundled code...");
-            let compile_result = std::process::Command::new("rustc")
-                .args(&[
-                    "bundled/solution.rs",
-                    "-o",
-                    "bundled/solution_test",
-                    "--allow",
-                    "warnings",
-                ])
-                .status()?;
-
-            if compile_result.success() {
+            check_rustc_version(&judge);
+            if verify_bundle(&output_dir.join(&filename), &judge)? {
                 println!("Bundled code compiles successfully!");
-
-                // Clean up test binary
-                let _ = std::fs::remove_file("bundled/solution_test");
             } else {
                 println!("WARNING: Bundled code has compilation issues");
             }